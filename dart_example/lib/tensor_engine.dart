@@ -29,18 +29,35 @@ typedef TensorMatmulDart = TensorHandle Function(TensorHandle lhs, TensorHandle
 typedef TensorDataPtrC = ffi.Pointer<ffi.Float> Function(TensorHandle tensor);
 typedef TensorDataPtrDart = ffi.Pointer<ffi.Float> Function(TensorHandle tensor);
 
+// tensor_copy_to_f32(tensor, out, len) -> i64
+typedef TensorCopyToF32C = ffi.Int64 Function(TensorHandle tensor, ffi.Pointer<ffi.Float> out, ffi.IntPtr len);
+typedef TensorCopyToF32Dart = int Function(TensorHandle tensor, ffi.Pointer<ffi.Float> out, int len);
+
 // tensor_get_shape(tensor, out_ndim)
 typedef TensorGetShapeC = ffi.Pointer<ffi.IntPtr> Function(TensorHandle tensor, ffi.Pointer<ffi.IntPtr> outNdim);
 typedef TensorGetShapeDart = ffi.Pointer<ffi.IntPtr> Function(TensorHandle tensor, ffi.Pointer<ffi.IntPtr> outNdim);
 
-// tensor_backward(root)
-typedef TensorBackwardC = ffi.Void Function(TensorHandle root);
-typedef TensorBackwardDart = void Function(TensorHandle root);
+// tensor_backward(root) -> i32 (0 on success)
+typedef TensorBackwardC = ffi.Int32 Function(TensorHandle root);
+typedef TensorBackwardDart = int Function(TensorHandle root);
 
 // tensor_grad(tensor) -> *mut Tensor
 typedef TensorGradC = TensorHandle Function(TensorHandle tensor);
 typedef TensorGradDart = TensorHandle Function(TensorHandle tensor);
 
+// tensor_last_error() -> *const c_char
+typedef TensorLastErrorC = ffi.Pointer<Utf8> Function();
+typedef TensorLastErrorDart = ffi.Pointer<Utf8> Function();
+
+/// Thrown when an engine call fails; carries the message from `tensor_last_error`.
+class TensorEngineException implements Exception {
+  final String message;
+  TensorEngineException(this.message);
+
+  @override
+  String toString() => 'TensorEngineException: $message';
+}
+
 // --- Wrapper Class ---
 
 class TensorEngine {
@@ -50,9 +67,11 @@ class TensorEngine {
   late TensorFreeDart _tensorFree;
   late TensorMatmulDart _tensorMatmul;
   late TensorDataPtrDart _tensorDataPtr;
+  late TensorCopyToF32Dart _tensorCopyToF32;
   late TensorGetShapeDart _tensorGetShape;
   late TensorBackwardDart _tensorBackward;
   late TensorGradDart _tensorGrad;
+  late TensorLastErrorDart _tensorLastError;
 
   TensorEngine(String libPath) {
     _dylib = ffi.DynamicLibrary.open(libPath);
@@ -73,10 +92,18 @@ class TensorEngine {
         .lookup<ffi.NativeFunction<TensorDataPtrC>>('tensor_data_ptr')
         .asFunction();
         
+    _tensorCopyToF32 = _dylib
+        .lookup<ffi.NativeFunction<TensorCopyToF32C>>('tensor_copy_to_f32')
+        .asFunction();
+
     _tensorGetShape = _dylib
         .lookup<ffi.NativeFunction<TensorGetShapeC>>('tensor_get_shape')
         .asFunction();
 
+    _tensorLastError = _dylib
+        .lookup<ffi.NativeFunction<TensorLastErrorC>>('tensor_last_error')
+        .asFunction();
+
     try {
         _tensorBackward = _dylib
             .lookup<ffi.NativeFunction<TensorBackwardC>>('tensor_backward')
@@ -89,6 +116,18 @@ class TensorEngine {
     }
   }
 
+  // Raises the engine's last error for a failed call.
+  Never _fail(String call) {
+    final message = _tensorLastError();
+    throw TensorEngineException(
+        message == ffi.nullptr ? '$call failed' : '$call: ${message.toDartString()}');
+  }
+
+  TensorHandle _checked(TensorHandle tensor, String call) {
+    if (tensor == ffi.nullptr) _fail(call);
+    return tensor;
+  }
+
   TensorHandle createTensor(List<double> data, List<int> shape) {
     final dataPtr = calloc<ffi.Float>(data.length);
     for (var i = 0; i < data.length; i++) {
@@ -105,7 +144,7 @@ class TensorEngine {
     calloc.free(dataPtr);
     calloc.free(shapePtr);
     
-    return tensor;
+    return _checked(tensor, 'tensor_create_f32');
   }
 
   void freeTensor(TensorHandle tensor) {
@@ -115,7 +154,7 @@ class TensorEngine {
   }
   
   TensorHandle matmul(TensorHandle lhs, TensorHandle rhs) {
-    return _tensorMatmul(lhs, rhs);
+    return _checked(_tensorMatmul(lhs, rhs), 'tensor_matmul');
   }
   
   List<double> getData(TensorHandle tensor) {
@@ -124,6 +163,10 @@ class TensorEngine {
       // Get shape to know size
       final ndimPtr = calloc<ffi.IntPtr>();
       final shapePtr = _tensorGetShape(tensor, ndimPtr);
+      if (shapePtr == ffi.nullptr) {
+          calloc.free(ndimPtr);
+          _fail('tensor_get_shape');
+      }
       final ndim = ndimPtr.value;
       
       int numel = 1;
@@ -142,19 +185,30 @@ class TensorEngine {
       
       calloc.free(ndimPtr);
       
-      final dataPtr = _tensorDataPtr(tensor);
+      // Copy out through the engine so strided views come back in row-major order
+      final dataPtr = calloc<ffi.Float>(numel);
+      final written = _tensorCopyToF32(tensor, dataPtr, numel);
+      if (written < 0) {
+          calloc.free(dataPtr);
+          _fail('tensor_copy_to_f32');
+      }
       final result = <double>[];
-      for (int i = 0; i < numel; i++) {
+      for (int i = 0; i < written; i++) {
           result.add(dataPtr[i]);
       }
+      calloc.free(dataPtr);
       return result;
   }
   
   void backward(TensorHandle root) {
-      _tensorBackward(root);
+      if (_tensorBackward(root) != 0) {
+          _fail('tensor_backward');
+      }
   }
   
-  TensorHandle grad(TensorHandle tensor) {
-      return _tensorGrad(tensor);
+  /// The tensor's gradient, or null if it has none.
+  TensorHandle? grad(TensorHandle tensor) {
+      final grad = _tensorGrad(tensor);
+      return grad == ffi.nullptr ? null : grad;
   }
 }
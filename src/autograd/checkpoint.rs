@@ -0,0 +1,98 @@
+use std::fmt;
+use std::sync::Arc;
+use crate::autograd::engine::{self, BackwardOptions};
+use crate::autograd::grad_mode::{enable_grad, is_grad_enabled, needs_grad, no_grad};
+use crate::autograd::node::Node;
+use crate::tensor::Tensor;
+
+type Segment = Arc<dyn Fn(&[Tensor]) -> Tensor + Send + Sync>;
+
+/// Runs `f(inputs)` without keeping its intermediate activations alive.
+///
+/// The forward pass runs under `no_grad`, so Nodes inside the segment (and the tensors they
+/// cache) are freed immediately. The returned tensor holds a single Node that keeps only
+/// `inputs` and `f`; during backward it re-runs `f` with grad enabled and backpropagates
+/// through the rebuilt segment. Trades one extra forward of the segment for memory.
+///
+/// `f` must be deterministic: the recomputed output is assumed equal to the original.
+/// Backward with `create_graph` records the recomputation, so the gradients can be
+/// differentiated again.
+pub fn checkpoint<F>(f: F, inputs: &[Tensor]) -> Tensor
+where
+    F: Fn(&[Tensor]) -> Tensor + Send + Sync + 'static,
+{
+    let output = {
+        let _guard = no_grad();
+        f(inputs)
+    };
+
+    let refs: Vec<&Tensor> = inputs.iter().collect();
+    if needs_grad(&refs) {
+        // Fresh identity, in case `f` returned (a clone of) one of its inputs
+        let mut out = output.detach();
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(CheckpointNode { function: Arc::new(f), inputs: inputs.to_vec() }));
+        return out;
+    }
+
+    output
+}
+
+pub struct CheckpointNode {
+    function: Segment,
+    inputs: Vec<Tensor>,
+}
+
+impl fmt::Debug for CheckpointNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckpointNode").field("inputs", &self.inputs).finish_non_exhaustive()
+    }
+}
+
+impl Node for CheckpointNode {
+    fn parents(&self) -> Vec<Tensor> {
+        self.inputs.clone()
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // The engine leaves grad mode on during backward only under `create_graph`
+        let create_graph = is_grad_enabled();
+        // Stand-ins for the inputs: the nested pass stops at them and leaves the outer graph
+        // (and the inputs' own gradients and hooks) untouched. Under `create_graph` they are
+        // recorded views of the inputs, so the gradients stay differentiable with respect to them.
+        let leaves: Vec<Tensor> = self
+            .inputs
+            .iter()
+            .map(|t| {
+                if create_graph {
+                    return t.reshape(&t.shape);
+                }
+                let mut leaf = t.detach();
+                leaf.requires_grad = t.requires_grad;
+                leaf
+            })
+            .collect();
+
+        let output = {
+            let _guard = enable_grad();
+            (self.function)(&leaves)
+        };
+
+        let wanted: Vec<Tensor> = leaves.iter().filter(|t| t.requires_grad).cloned().collect();
+        let mut grads = if output.requires_grad && !wanted.is_empty() {
+            engine::grad(&output, &wanted, grad, BackwardOptions { create_graph })
+                .unwrap_or_else(|e| panic!("checkpoint recomputation failed: {}", e))
+        } else {
+            Vec::new()
+        }
+        .into_iter();
+
+        leaves
+            .iter()
+            .map(|leaf| {
+                let zeros = || Tensor::zeros(leaf.shape.clone(), leaf.dtype);
+                if leaf.requires_grad { grads.next().unwrap_or_else(zeros) } else { zeros() }
+            })
+            .collect()
+    }
+}
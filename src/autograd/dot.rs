@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::Path;
+use crate::error::Result;
+use crate::tensor::Tensor;
+
+/// Renders the graph behind `root` in Graphviz DOT format.
+///
+/// Tensors are boxes labelled with id, shape, dtype and `requires_grad`; ops are ellipses
+/// labelled with the Node name. Edges follow data flow (input -> op -> output).
+/// Traversal mirrors the DFS in `engine::backward`, so the picture is what backward will walk.
+pub fn export_dot(root: &Tensor) -> String {
+    let mut out = String::from("digraph autograd {\n    rankdir=LR;\n    node [fontname=\"monospace\"];\n");
+    let mut visited = HashSet::new();
+    write_tensor(root, &mut visited, &mut out);
+    out.push_str("}\n");
+    out
+}
+
+/// Writes `export_dot(root)` to `path`, e.g. for `dot -Tsvg graph.dot -o graph.svg`.
+pub fn write_dot(root: &Tensor, path: impl AsRef<Path>) -> Result<()> {
+    std::fs::write(path, export_dot(root))?;
+    Ok(())
+}
+
+fn write_tensor(tensor: &Tensor, visited: &mut HashSet<usize>, out: &mut String) {
+    if !visited.insert(tensor.id) {
+        return;
+    }
+
+    // Leaves that will receive gradients are highlighted
+    let style = if tensor.requires_grad && tensor.ctx.is_none() { ", style=filled, fillcolor=lightblue" } else { "" };
+    let _ = writeln!(
+        out,
+        "    t{id} [shape=box, label=\"#{id}\\n{shape:?} {dtype:?}\\nrequires_grad={rg}\"{style}];",
+        id = tensor.id,
+        shape = tensor.shape,
+        dtype = tensor.dtype,
+        rg = tensor.requires_grad,
+    );
+
+    if let Some(ctx) = &tensor.ctx {
+        let _ = writeln!(out, "    op{} [shape=ellipse, label=\"{}\"];", tensor.id, ctx.name());
+        let _ = writeln!(out, "    op{} -> t{};", tensor.id, tensor.id);
+        for parent in ctx.parents() {
+            write_tensor(&parent, visited, out);
+            let _ = writeln!(out, "    t{} -> op{};", parent.id, tensor.id);
+        }
+    }
+}
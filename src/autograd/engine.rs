@@ -1,9 +1,98 @@
-use crate::tensor::Tensor;
-use std::collections::HashSet;
+use crate::tensor::{Tensor, DType};
+use crate::error::{Result, TensorError};
+use crate::autograd::grad_mode::{enable_grad, is_inference_mode, no_grad};
+use crate::ops::binary::add;
+use std::collections::{HashMap, HashSet};
 
-/// Runs the backward pass starting from `root`.
-/// `root` is usually the loss value (scalar).
-pub fn backward(root: &Tensor) {
+/// Runs the backward pass starting from a scalar `root` (usually the loss),
+/// seeding its gradient with 1.
+pub fn backward(root: &Tensor) -> Result<()> {
+    if root.numel() != 1 {
+        return Err(TensorError::ShapeMismatch { expected: vec![], found: root.shape.clone() });
+    }
+    backward_with_grad(root, &Tensor::ones(root.shape.clone(), root.dtype))
+}
+
+/// Options for [`backward_with_options`] and [`grad`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BackwardOptions {
+    /// Record the backward pass itself, so the resulting gradients can be differentiated
+    /// again (Hessian-vector products, gradient penalties). Gradients are then accumulated
+    /// out of place with `add`; only F32 graphs are supported.
+    /// When false, Node backward functions run under `no_grad` and record nothing.
+    pub create_graph: bool,
+}
+
+/// Runs the backward pass with an explicit `grad_output` for `root`, which may be non-scalar.
+/// This computes the vector-Jacobian product `grad_output^T * d(root)/d(leaves)`.
+///
+/// Gradients are accumulated into leaves with `requires_grad` and into tensors marked with
+/// `retain_grad`; other intermediate gradients are dropped once propagated. Hooks registered
+/// with `register_hook` see each tensor's gradient from this pass before it is used.
+pub fn backward_with_grad(root: &Tensor, grad_output: &Tensor) -> Result<()> {
+    backward_with_options(root, grad_output, BackwardOptions::default())
+}
+
+/// [`backward_with_grad`] with explicit [`BackwardOptions`].
+pub fn backward_with_options(root: &Tensor, grad_output: &Tensor, options: BackwardOptions) -> Result<()> {
+    run_backward(root, grad_output, options, None).map(|_| ())
+}
+
+/// Returns `d(output)/d(inputs)` contracted with `grad_output`, one gradient per input,
+/// without touching any tensor's stored gradient. Inputs may be intermediate tensors;
+/// inputs `output` does not depend on get zeros. Only the part of the graph between `output`
+/// and `inputs` is run.
+///
+/// With `create_graph`, the returned gradients are part of the graph and can be fed to
+/// another `grad` or `backward` call for higher-order derivatives.
+pub fn grad(output: &Tensor, inputs: &[Tensor], grad_output: &Tensor, options: BackwardOptions) -> Result<Vec<Tensor>> {
+    if inputs.iter().any(|t| !t.requires_grad) {
+        return Err(TensorError::NoGraph);
+    }
+    let mut captured = run_backward(output, grad_output, options, Some(inputs))?;
+    Ok(inputs
+        .iter()
+        .map(|t| captured.remove(&t.id).unwrap_or_else(|| Tensor::zeros(t.shape.clone(), t.dtype)))
+        .collect())
+}
+
+/// Adds `grad` into `slot`: in place on a private copy, or through the `add` op when
+/// either side is recorded and the pass builds a graph.
+fn accumulate(slot: &mut Option<Tensor>, grad: Tensor, create_graph: bool) {
+    let recorded = grad.requires_grad || slot.as_ref().is_some_and(|g| g.requires_grad);
+    if !(create_graph && recorded) {
+        Tensor::accumulate_grad(slot, grad);
+        return;
+    }
+    *slot = Some(match slot.take() {
+        Some(existing) => add(&existing, &grad),
+        None => grad,
+    });
+}
+
+/// Shared reverse pass. With `capture`, gradients of those tensors are returned by id
+/// instead of being stored on leaves / retained tensors.
+fn run_backward(root: &Tensor, grad_output: &Tensor, options: BackwardOptions, capture: Option<&[Tensor]>) -> Result<HashMap<usize, Tensor>> {
+    if is_inference_mode() {
+        return Err(TensorError::Unsupported("backward inside inference_mode".to_string()));
+    }
+    if !root.requires_grad {
+        return Err(TensorError::NoGraph);
+    }
+    if grad_output.shape != root.shape {
+        return Err(TensorError::ShapeMismatch { expected: root.shape.clone(), found: grad_output.shape.clone() });
+    }
+    if grad_output.dtype != root.dtype {
+        return Err(TensorError::DTypeMismatch { expected: root.dtype, found: grad_output.dtype });
+    }
+    if options.create_graph && root.dtype != DType::F32 {
+        return Err(TensorError::Unsupported(format!("create_graph on {:?}", root.dtype)));
+    }
+    let create_graph = options.create_graph;
+    // Recording must not depend on the caller's grad mode
+    let _recording = create_graph.then(enable_grad);
+    let capture: Option<HashSet<usize>> = capture.map(|inputs| inputs.iter().map(|t| t.id).collect());
+    let mut captured = HashMap::new();
     // 1. Topological Sort
     let mut sorted_nodes = Vec::new();
     let mut visited = HashSet::new();
@@ -32,42 +121,87 @@ pub fn backward(root: &Tensor) {
 
     dfs(root, &mut visited, &mut sorted_nodes);
 
-    // 2. Initialize Gradient of Root to 1.0 (if not set)
-    // We assume root is a scalar F32 for now.
-    {
-        // TODO: check if root is scalar
-        // let one = Tensor::ones(root.shape(), root.dtype());
-        // root.accumulate_grad(one);
-        // For now, let's assume the user seeds the gradient or we do it here.
-        // If we implement `ones` later, we can use it.
-        // For now, let's just make the user manually seed logic or placeholder.
-        // Wait, I should implement `ones` capability or `accumulate_grad`.
-    }
+    // When capturing, only the part of the graph that leads to a captured tensor is needed;
+    // nothing flows past the captured tensors into the rest of the graph.
+    let leads_to_capture: Option<HashSet<usize>> = capture.as_ref().map(|ids| {
+        let mut reach = ids.clone();
+        for tensor in &sorted_nodes {
+            if tensor.ctx.as_ref().is_some_and(|ctx| ctx.parents().iter().any(|p| reach.contains(&p.id))) {
+                reach.insert(tensor.id);
+            }
+        }
+        reach
+    });
+
+    // 2. Seed the root
+    // Gradients for this pass live here until each tensor is reached; only then are they
+    // hooked, stored (leaves / retain_grad) and propagated, so earlier passes never leak in.
+    let mut pending: HashMap<usize, Option<Tensor>> = HashMap::new();
+    accumulate(pending.entry(root.id).or_default(), grad_output.clone(), create_graph);
 
     // 3. Reverse Iterate and Propagate
     for tensor in sorted_nodes.iter().rev() {
-        let grad = {
-            let lock = tensor.grad.read().unwrap();
-            if lock.is_none() { 
-                continue; 
-            }
-            lock.as_ref().unwrap().clone()
+        let mut grad = match pending.remove(&tensor.id).flatten() {
+            Some(grad) => grad,
+            None => continue,
         };
 
+        for hook in tensor.hooks.read().unwrap().iter() {
+            if let Some(new_grad) = hook(&grad) {
+                if new_grad.shape != grad.shape {
+                    return Err(TensorError::ShapeMismatch { expected: grad.shape.clone(), found: new_grad.shape.clone() });
+                }
+                if new_grad.dtype != grad.dtype {
+                    return Err(TensorError::DTypeMismatch { expected: grad.dtype, found: new_grad.dtype });
+                }
+                grad = new_grad;
+            }
+        }
+
+        match &capture {
+            Some(ids) => {
+                if ids.contains(&tensor.id) {
+                    captured.insert(tensor.id, grad.clone());
+                }
+            }
+            None => {
+                if tensor.retains_grad() {
+                    let mut lock = tensor.grad.write().unwrap();
+                    accumulate(&mut lock, grad.clone(), create_graph);
+                }
+            }
+        }
+
         if let Some(ctx) = &tensor.ctx {
-            let grads = ctx.backward(&grad);
             let parents = ctx.parents();
-            
+            if leads_to_capture.as_ref().is_some_and(|reach| !parents.iter().any(|p| reach.contains(&p.id))) {
+                continue;
+            }
+            let grads = if create_graph {
+                ctx.backward(&grad)
+            } else {
+                let _guard = no_grad();
+                ctx.backward(&grad)
+            };
+
+            // Custom Nodes / Functions can get this wrong; fail the pass instead of panicking
             if grads.len() != parents.len() {
-                panic!("Gradient count mismatch for node {:?}", ctx);
+                return Err(TensorError::GradientCount { node: ctx.name(), expected: parents.len(), found: grads.len() });
             }
 
-            for (parent, parent_grad) in parents.iter().zip(grads.into_iter()) {
+            for (parent, parent_grad) in parents.iter().zip(grads) {
                 if parent.requires_grad {
-                   parent.add_grad(parent_grad);
+                    if parent_grad.shape != parent.shape {
+                        return Err(TensorError::ShapeMismatch { expected: parent.shape.clone(), found: parent_grad.shape.clone() });
+                    }
+                    if parent_grad.dtype != parent.dtype {
+                        return Err(TensorError::DTypeMismatch { expected: parent.dtype, found: parent_grad.dtype });
+                    }
+                    accumulate(pending.entry(parent.id).or_default(), parent_grad, create_graph);
                 }
             }
         }
     }
-}
 
+    Ok(captured)
+}
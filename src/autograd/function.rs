@@ -0,0 +1,132 @@
+use std::fmt;
+use std::sync::Arc;
+use crate::autograd::grad_mode::{needs_grad, no_grad};
+use crate::autograd::node::Node;
+use crate::tensor::Tensor;
+
+/// A user-defined differentiable op.
+///
+/// Implement `forward` and `backward`, then call [`Function::apply`] (or [`apply`]); the graph
+/// wiring (`requires_grad`, the Node behind the output) is handled here, so custom kernels
+/// never touch tensor internals.
+///
+/// ```
+/// use edge_tensor_engine::Tensor;
+/// use edge_tensor_engine::autograd::{Function, FunctionCtx};
+/// use edge_tensor_engine::ops::binary::{mul, mul_scalar};
+///
+/// struct Square;
+///
+/// impl Function for Square {
+///     fn forward(&self, ctx: &mut FunctionCtx, inputs: &[Tensor]) -> Tensor {
+///         ctx.save_for_backward(&inputs[..1]);
+///         mul(&inputs[0], &inputs[0])
+///     }
+///
+///     fn backward(&self, ctx: &FunctionCtx, grad: &Tensor) -> Vec<Tensor> {
+///         let x = &ctx.saved_tensors()[0];
+///         vec![mul(grad, &mul_scalar(x, 2.0))]
+///     }
+/// }
+///
+/// let y = Square.apply(&[Tensor::from_vec_f32(vec![3.0], vec![1])]);
+/// assert_eq!(y.item::<f32>().unwrap(), 9.0);
+/// ```
+pub trait Function: Send + Sync + 'static {
+    /// Computes the output. Runs under `no_grad`: ops used here are not recorded.
+    fn forward(&self, ctx: &mut FunctionCtx, inputs: &[Tensor]) -> Tensor;
+
+    /// Returns one gradient per input, each with that input's shape and dtype.
+    /// Gradients for inputs where `ctx.needs_input_grad(i)` is false are ignored (zeros are fine).
+    /// A wrong count, shape or dtype fails the backward pass with an error.
+    fn backward(&self, ctx: &FunctionCtx, grad: &Tensor) -> Vec<Tensor>;
+
+    /// Runs the function on `inputs`, recording it in the graph when any input requires grad.
+    fn apply(self, inputs: &[Tensor]) -> Tensor
+    where
+        Self: Sized,
+    {
+        apply(self, inputs)
+    }
+}
+
+/// State carried from [`Function::forward`] to [`Function::backward`].
+#[derive(Debug, Default)]
+pub struct FunctionCtx {
+    saved: Vec<Tensor>,
+    needs_input_grad: Vec<bool>,
+}
+
+impl FunctionCtx {
+    /// Keeps `tensors` alive for backward. Replaces anything saved earlier.
+    pub fn save_for_backward(&mut self, tensors: &[Tensor]) {
+        self.saved = tensors.to_vec();
+    }
+
+    /// Tensors stored by [`save_for_backward`](Self::save_for_backward), in the same order.
+    pub fn saved_tensors(&self) -> &[Tensor] {
+        &self.saved
+    }
+
+    /// Whether input `index` will receive the gradient returned for it.
+    pub fn needs_input_grad(&self, index: usize) -> bool {
+        self.needs_input_grad.get(index).copied().unwrap_or(false)
+    }
+}
+
+/// Runs `function` on `inputs`; see [`Function`].
+pub fn apply<F: Function>(function: F, inputs: &[Tensor]) -> Tensor {
+    let refs: Vec<&Tensor> = inputs.iter().collect();
+    let record = needs_grad(&refs);
+
+    let mut ctx = FunctionCtx {
+        saved: Vec::new(),
+        needs_input_grad: inputs.iter().map(|t| record && t.requires_grad).collect(),
+    };
+    let output = {
+        let _guard = no_grad();
+        function.forward(&mut ctx, inputs)
+    };
+
+    if record {
+        // Fresh identity, in case forward returned (a clone of) one of its inputs
+        let mut out = output.detach();
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(FunctionNode { function, ctx, inputs: inputs.to_vec() }));
+        return out;
+    }
+
+    output
+}
+
+pub struct FunctionNode<F> {
+    function: F,
+    ctx: FunctionCtx,
+    inputs: Vec<Tensor>,
+}
+
+impl<F> fmt::Debug for FunctionNode<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionNode")
+            .field("function", &std::any::type_name::<F>())
+            .field("inputs", &self.inputs)
+            .field("saved", &self.ctx.saved)
+            .finish()
+    }
+}
+
+impl<F: Function> Node for FunctionNode<F> {
+    fn parents(&self) -> Vec<Tensor> {
+        self.inputs.clone()
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        self.function.backward(&self.ctx, grad)
+    }
+
+    /// Named after the user's Function type rather than this wrapper.
+    fn name(&self) -> &'static str {
+        let full = std::any::type_name::<F>();
+        full.rsplit("::").next().unwrap_or(full)
+    }
+}
@@ -0,0 +1,72 @@
+use std::cell::Cell;
+use std::marker::PhantomData;
+use crate::tensor::Tensor;
+
+// Thread-local switches deciding whether ops record the graph.
+// Each guard restores the previous state on drop, so guards nest.
+
+thread_local! {
+    static GRAD_ENABLED: Cell<bool> = const { Cell::new(true) };
+    static INFERENCE_MODE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether ops on this thread currently record the graph.
+pub fn is_grad_enabled() -> bool {
+    GRAD_ENABLED.with(|g| g.get())
+}
+
+/// Whether this thread is inside an `inference_mode` scope.
+pub fn is_inference_mode() -> bool {
+    INFERENCE_MODE.with(|m| m.get())
+}
+
+/// True when an op over `inputs` should build a Node.
+pub(crate) fn needs_grad(inputs: &[&Tensor]) -> bool {
+    is_grad_enabled() && inputs.iter().any(|t| t.requires_grad)
+}
+
+/// RAII guard returned by [`no_grad`]. Not `Send`: the mode is per-thread.
+pub struct NoGradGuard {
+    prev: bool,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for NoGradGuard {
+    fn drop(&mut self) {
+        GRAD_ENABLED.with(|g| g.set(self.prev));
+    }
+}
+
+/// Disables graph recording on this thread until the guard is dropped.
+/// Outputs of ops never require grad and inputs are not kept alive by Nodes.
+pub fn no_grad() -> NoGradGuard {
+    let prev = GRAD_ENABLED.with(|g| g.replace(false));
+    NoGradGuard { prev, _not_send: PhantomData }
+}
+
+/// Re-enables graph recording on this thread until the guard is dropped,
+/// e.g. to rebuild part of the graph from inside a `no_grad` scope.
+/// Has no effect on `inference_mode`, which still refuses to run `backward`.
+pub fn enable_grad() -> NoGradGuard {
+    let prev = GRAD_ENABLED.with(|g| g.replace(true));
+    NoGradGuard { prev, _not_send: PhantomData }
+}
+
+/// RAII guard returned by [`inference_mode`].
+pub struct InferenceModeGuard {
+    _no_grad: NoGradGuard,
+    prev: bool,
+}
+
+impl Drop for InferenceModeGuard {
+    fn drop(&mut self) {
+        INFERENCE_MODE.with(|m| m.set(self.prev));
+    }
+}
+
+/// Stronger form of [`no_grad`] for deployment: graph recording is off and
+/// `backward` refuses to run, so no autograd state is touched on this thread.
+pub fn inference_mode() -> InferenceModeGuard {
+    let prev = INFERENCE_MODE.with(|m| m.replace(true));
+    InferenceModeGuard { _no_grad: no_grad(), prev }
+}
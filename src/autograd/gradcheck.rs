@@ -0,0 +1,94 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::autograd::engine::backward_with_grad;
+use crate::autograd::grad_mode::no_grad;
+use crate::error::{Result, TensorError};
+use crate::tensor::{Tensor, DType};
+
+/// Checks the analytic gradients of `f` against central finite differences.
+///
+/// The output is reduced to a scalar as `sum(f(inputs) * w)` with a fixed random `w`, so every
+/// output element contributes. Only inputs with `requires_grad` are checked. Each element is
+/// accepted when `|analytic - numerical| <= tol * (1 + |numerical|)`.
+///
+/// `f` is evaluated on private copies of `inputs`, which are never modified.
+pub fn gradcheck<F>(f: F, inputs: &[Tensor], eps: f32, tol: f32) -> Result<()>
+where
+    F: Fn(&[Tensor]) -> Tensor,
+{
+    for input in inputs {
+        if input.dtype != DType::F32 {
+            return Err(TensorError::DTypeMismatch { expected: DType::F32, found: input.dtype });
+        }
+    }
+
+    // Fresh dense leaves so gradients start empty and perturbations stay local
+    let leaves: Vec<Tensor> = inputs
+        .iter()
+        .map(|t| {
+            let mut leaf = Tensor::zeros(t.shape.clone(), DType::F32);
+            unsafe { leaf.copy_from(t) };
+            leaf.requires_grad_(t.requires_grad);
+            leaf
+        })
+        .collect();
+
+    let output = f(&leaves);
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    let weights: Vec<f32> = (0..output.numel()).map(|_| rng.gen_range(-1.0..1.0)).collect();
+    let weights = Tensor::from_vec_f32(weights, output.shape.clone());
+
+    // Analytic: one backward pass with `w` as the output gradient
+    backward_with_grad(&output, &weights)?;
+
+    let w = weights.to_vec_f32()?;
+    let objective = |leaves: &[Tensor]| -> f64 {
+        let _guard = no_grad();
+        let out = f(leaves).to_vec_f32().expect("gradcheck output must be F32");
+        out.iter().zip(w.iter()).map(|(&o, &w)| o as f64 * w as f64).sum()
+    };
+
+    for (input_idx, leaf) in leaves.iter().enumerate() {
+        if !leaf.requires_grad {
+            continue;
+        }
+        let analytic = match leaf.grad.read().unwrap().as_ref() {
+            Some(g) => g.to_vec_f32()?,
+            None => vec![0.0; leaf.numel()], // Output does not depend on this input
+        };
+
+        // Leaves are dense, so flat positions map to row-major indices
+        let data = leaf.as_slice::<f32>()?.to_vec();
+        for (flat, &original) in data.iter().enumerate() {
+            let index = unravel(flat, &leaf.shape);
+
+            // Leaves are private to this check and nothing borrows their storage between calls
+            unsafe { leaf.set(&index, original + eps)? };
+            let plus = objective(&leaves);
+            unsafe { leaf.set(&index, original - eps)? };
+            let minus = objective(&leaves);
+            unsafe { leaf.set(&index, original)? };
+
+            let numerical = ((plus - minus) / (2.0 * eps as f64)) as f32;
+            if (analytic[flat] - numerical).abs() > tol * (1.0 + numerical.abs()) {
+                return Err(TensorError::GradientMismatch {
+                    input: input_idx,
+                    index,
+                    analytic: analytic[flat],
+                    numerical,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn unravel(mut flat: usize, shape: &[usize]) -> Vec<usize> {
+    let mut index = vec![0; shape.len()];
+    for d in (0..shape.len()).rev() {
+        index[d] = flat % shape[d];
+        flat /= shape[d];
+    }
+    index
+}
@@ -1,9 +1,20 @@
+pub mod checkpoint;
+pub mod dot;
 pub mod engine;
+pub mod function;
+pub mod gradcheck;
+pub mod grad_mode;
 pub mod node;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 pub mod tests;
 
-pub use engine::backward;
+pub use checkpoint::checkpoint;
+pub use dot::{export_dot, write_dot};
+pub use engine::{backward, backward_with_grad, backward_with_options, grad, BackwardOptions};
+pub use function::{apply, Function, FunctionCtx};
+pub use gradcheck::gradcheck;
+pub use grad_mode::{enable_grad, inference_mode, is_grad_enabled, is_inference_mode, no_grad, InferenceModeGuard, NoGradGuard};
 pub use node::Node;
 
@@ -10,4 +10,11 @@ pub trait Node: Debug + Send + Sync {
     /// Computes gradients for parents given the gradient of the output.
     /// Returns a vector of gradients, one for each parent, in the same order.
     fn backward(&self, grad: &Tensor) -> Vec<Tensor>;
+
+    /// Short op name for diagnostics (graph export, error messages).
+    /// Defaults to the implementing type's name without its module path.
+    fn name(&self) -> &'static str {
+        let full = std::any::type_name::<Self>();
+        full.rsplit("::").next().unwrap_or(full)
+    }
 }
@@ -1,7 +1,19 @@
 mod tests {
-    use crate::tensor::Tensor;
-    use crate::ops::binary::add;
-    use crate::autograd::backward;
+    use crate::tensor::{Tensor, DType};
+    use crate::ops::binary::{add, sub, mul, div, pow, maximum, minimum, add_scalar, mul_scalar, div_scalar, pow_scalar};
+    use crate::autograd::{checkpoint, enable_grad, grad, gradcheck, BackwardOptions, Function, FunctionCtx, Node};
+    use crate::autograd::backward_with_options;
+    use crate::ops::reduce::{sum, mean, max, min, prod};
+    use crate::ops::softmax::{softmax, log_softmax};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use std::sync::Arc;
+    use crate::ops::matmul::matmul;
+    use crate::ops::unary::{relu, leaky_relu, sigmoid, tanh, silu, gelu, gelu_tanh, softplus};
+    use crate::ops::unary::{exp, log, sqrt, rsqrt, abs, neg, sin, cos, clamp, reciprocal};
+    use crate::nn::linear::Linear;
+    use crate::autograd::{backward, backward_with_grad, no_grad, inference_mode, is_grad_enabled};
+    use crate::TensorError;
 
     #[test]
     fn test_autograd_add() {
@@ -28,12 +40,777 @@ mod tests {
         let z_grad = z.grad.read().unwrap();
         assert!(z_grad.is_some());
         
+        assert_eq!(z_grad.as_ref().unwrap().item::<f32>().unwrap(), 1.0);
+        
+    }
+
+    fn grad_of(t: &Tensor) -> Vec<f32> {
+        t.grad.read().unwrap().as_ref().expect("missing grad").to_vec_f32().unwrap()
+    }
+
+    #[test]
+    fn test_clone_shares_identity() {
+        let mut x = Tensor::from_vec_f32(vec![1.0], vec![1]);
+        x.requires_grad = true;
+        let x2 = x.clone();
+        assert_eq!(x.id(), x2.id());
+
+        x2.add_grad(Tensor::from_vec_f32(vec![5.0], vec![1]));
+        assert_eq!(grad_of(&x), vec![5.0]);
+    }
+
+    #[test]
+    fn test_mlp_backward() {
+        // y = relu(x @ W1 + b1) @ W2, batch of 2
+        let x = Tensor::from_vec_f32(vec![1.0, 2.0, 0.0, 1.0], vec![2, 2]);
+        let mut w1 = Tensor::from_vec_f32(vec![1.0, -1.0, 0.5, 1.0], vec![2, 2]);
+        w1.requires_grad = true;
+        let mut b1 = Tensor::from_vec_f32(vec![0.0, -2.0], vec![2]);
+        b1.requires_grad = true;
+        let mut w2 = Tensor::from_vec_f32(vec![3.0, 4.0], vec![2, 1]);
+        w2.requires_grad = true;
+
+        let h = relu(&add(&matmul(&x, &w1), &b1)); // [[2, 0], [0.5, 0]]
+        let y = matmul(&h, &w2); // [[6], [1.5]]
+        assert_eq!(y.to_vec_f32().unwrap(), vec![6.0, 1.5]);
+
+        backward_with_grad(&y, &Tensor::ones(vec![2, 1], DType::F32)).unwrap();
+
+        assert_eq!(grad_of(&w2), vec![2.5, 0.0]);
+        assert_eq!(grad_of(&b1), vec![6.0, 0.0]);
+        assert_eq!(grad_of(&w1), vec![3.0, 0.0, 9.0, 0.0]);
+        // x does not require grad, so nothing is accumulated into it
+        assert!(x.grad.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_backward_through_transposed_weight() {
+        // Linear stores W as [Out, In] and multiplies by the W.t() view
+        let mut layer = Linear::new(2, 1, false);
+        layer.weight.requires_grad = true;
+        let x = Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]);
+
+        let y = layer.forward(&x);
+        backward_with_grad(&y, &Tensor::ones(vec![2, 1], DType::F32)).unwrap();
+
+        // dL/dW = sum over the batch of x
+        assert_eq!(grad_of(&layer.weight), vec![4.0, 6.0]);
+    }
+
+    #[test]
+    fn test_backward_seeds_scalar_root() {
+        let mut x = Tensor::from_vec_f32(vec![2.0, 3.0], vec![2]);
+        x.requires_grad = true;
+        let w = Tensor::from_vec_f32(vec![4.0, 5.0], vec![2, 1]);
+
+        let y = matmul(&x.unsqueeze(0), &w); // [1, 1]
+        y.retain_grad();
+        backward(&y).unwrap();
+        assert_eq!(grad_of(&y), vec![1.0]);
+        assert_eq!(grad_of(&x), vec![4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_backward_errors() {
+        let mut x = Tensor::from_vec_f32(vec![2.0, 3.0], vec![2]);
+        assert!(matches!(backward(&x), Err(TensorError::ShapeMismatch { .. })));
+
+        let s = x.narrow(0, 0, 1);
+        assert!(matches!(backward(&s), Err(TensorError::NoGraph)));
+
+        x.requires_grad = true;
+        let y = add(&x, &x);
+        let bad = Tensor::ones(vec![3], DType::F32);
+        assert!(matches!(backward_with_grad(&y, &bad), Err(TensorError::ShapeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_grad_accumulation() {
+        // x used twice: d(x * x)/dx = 2x, accumulated from both MulNode parents
+        let mut x = Tensor::from_vec_f32(vec![3.0, -1.0], vec![2]);
+        x.requires_grad = true;
+        let y = mul(&x, &x);
+        backward_with_grad(&y, &Tensor::ones(vec![2], DType::F32)).unwrap();
+        assert_eq!(grad_of(&x), vec![6.0, -2.0]);
+        // Accumulation must not touch the tensor's own data
+        assert_eq!(x.to_vec_f32().unwrap(), vec![3.0, -1.0]);
+
+        // A non-contiguous incoming gradient adds elementwise, and the stored grad owns its memory
+        let mut w = Tensor::zeros(vec![2, 2], DType::F32);
+        w.requires_grad = true;
+        let g = Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]);
+        w.add_grad(g.clone());
+        w.add_grad(g.t());
+        assert_eq!(grad_of(&w), vec![2.0, 5.0, 5.0, 8.0]);
+        assert_eq!(g.to_vec_f32().unwrap(), vec![1.0, 2.0, 3.0, 4.0]);
+
+        // A handle to the stored grad may be borrowing it, so later passes sum into a copy
+        let held = w.grad().unwrap();
+        let slice = held.as_slice::<f32>().unwrap();
+        w.add_grad(g.clone());
+        assert_eq!(slice, &[2.0, 5.0, 5.0, 8.0]);
+        assert_eq!(grad_of(&w), vec![3.0, 7.0, 8.0, 12.0]);
+
+        let h = Tensor::zeros(vec![2], DType::F16);
+        h.add_grad(Tensor::ones(vec![2], DType::F16));
+        h.add_grad(Tensor::ones(vec![2], DType::F16));
+        let hg = h.grad.read().unwrap().as_ref().unwrap().to_vec::<half::f16>().unwrap();
+        assert_eq!(hg[1].to_f32(), 2.0);
+    }
+
+    #[test]
+    fn test_no_grad_guard() {
+        let mut x = Tensor::from_vec_f32(vec![1.0, 2.0], vec![2]);
+        x.requires_grad_(true);
+
+        {
+            let _guard = no_grad();
+            assert!(!is_grad_enabled());
+            {
+                let _nested = no_grad();
+            }
+            // Dropping the inner guard restores the outer (disabled) state
+            assert!(!is_grad_enabled());
+
+            let y = relu(&mul(&x, &x).unsqueeze(0));
+            assert!(!y.requires_grad());
+            assert!(y.ctx.is_none());
+        }
+        assert!(is_grad_enabled());
+        assert!(mul(&x, &x).ctx.is_some());
+
+        let y = add(&x, &x);
+        {
+            let _guard = inference_mode();
+            assert!(!is_grad_enabled());
+            assert!(matches!(backward_with_grad(&y, &Tensor::ones(vec![2], DType::F32)), Err(TensorError::Unsupported(_))));
+        }
+        assert!(backward_with_grad(&y, &Tensor::ones(vec![2], DType::F32)).is_ok());
+    }
+
+    #[test]
+    fn test_detach() {
+        let mut x = Tensor::from_vec_f32(vec![1.0, 2.0], vec![2]);
+        x.requires_grad_(true);
+        let y = mul(&x, &x);
+
+        let d = y.detach();
+        assert!(!d.requires_grad());
+        assert!(d.ctx.is_none());
+        assert_ne!(d.id(), y.id());
+        assert!(std::sync::Arc::ptr_eq(&d.storage, &y.storage));
+        // Ops on a detached tensor do not reach x
+        assert!(mul(&d, &d).ctx.is_none());
+    }
+
+    // Leaf with values drawn uniformly from [lo, hi)
+    fn rand_leaf(shape: Vec<usize>, seed: u64, lo: f32, hi: f32) -> Tensor {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let data = (0..shape.iter().product()).map(|_| rng.gen_range(lo..hi)).collect();
+        let mut t = Tensor::from_vec_f32(data, shape);
+        t.requires_grad_(true);
+        t
+    }
+
+    const EPS: f32 = 1e-2;
+    const TOL: f32 = 1e-2;
+
+    #[test]
+    fn test_gradcheck_binary_ops() {
+        // Broadcast rhs exercises the gradient reduction; positive inputs keep div / pow smooth
+        let inputs = [rand_leaf(vec![2, 3], 1, 0.5, 2.0), rand_leaf(vec![3], 2, 0.5, 2.0)];
+        gradcheck(|x| add(&x[0], &x[1]), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| sub(&x[0], &x[1]), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| mul(&x[0], &x[1]), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| div(&x[0], &x[1]), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| pow(&x[0], &x[1]), &inputs, EPS, TOL).unwrap();
+        // Disjoint ranges avoid ties, where max / min are not differentiable
+        let inputs = [rand_leaf(vec![2, 3], 3, 0.0, 1.0), rand_leaf(vec![2, 1], 4, 0.0, 1.0)];
+        gradcheck(|x| maximum(&x[0], &x[1]), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| minimum(&x[0], &x[1]), &inputs, EPS, TOL).unwrap();
+    }
+
+    #[test]
+    fn test_gradcheck_scalar_ops() {
+        let inputs = [rand_leaf(vec![4], 5, 0.5, 2.0)];
+        gradcheck(|x| add_scalar(&x[0], 3.0), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| mul_scalar(&x[0], -2.0), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| div_scalar(&x[0], 4.0), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| pow_scalar(&x[0], 3.0), &inputs, EPS, TOL).unwrap();
+
+        // x^0 is constant: a zero gradient, also at x == 0 where n * x^(n-1) would be NaN
+        let mut x = Tensor::from_vec_f32(vec![0.0, 2.0], vec![2]);
+        x.requires_grad = true;
+        backward_with_grad(&pow_scalar(&x, 0.0), &Tensor::ones(vec![2], DType::F32)).unwrap();
+        assert_eq!(grad_of(&x), vec![0.0, 0.0]);
+
+        // F16 gradients stay F16
+        let mut h = Tensor::zeros(vec![2], DType::F16);
         unsafe {
-            let val = *(z_grad.as_ref().unwrap().storage.as_ptr() as *const f32);
-            assert_eq!(val, 1.0);
+            h.set(&[0], half::f16::from_f32(1.5)).unwrap();
+            h.set(&[1], half::f16::from_f32(-2.0)).unwrap();
         }
-        
-        // Note: Testing full backward propagation would require refactoring how
-        // tensor clones share gradient buffers, which is beyond this prototype's scope.
+        h.requires_grad = true;
+        backward_with_grad(&pow_scalar(&h, 2.0), &Tensor::ones(vec![2], DType::F16)).unwrap();
+        let g = h.grad().unwrap();
+        assert_eq!(g.dtype(), DType::F16);
+        assert_eq!(g.to_vec::<half::f16>().unwrap(), vec![half::f16::from_f32(3.0), half::f16::from_f32(-4.0)]);
+
+        // pow with a tensor exponent: d/dx at x == y == 0 is 0 rather than 0 * 0^-1 = NaN
+        let mut x = Tensor::from_vec_f32(vec![0.0, 2.0], vec![2]);
+        x.requires_grad = true;
+        let mut y = Tensor::from_vec_f32(vec![0.0, 3.0], vec![2]);
+        y.requires_grad = true;
+        backward_with_grad(&pow(&x, &y), &Tensor::ones(vec![2], DType::F32)).unwrap();
+        assert_eq!(grad_of(&x), vec![0.0, 12.0]);
+        // Also under create_graph, where the bump has to survive the nested pow
+        let gx = grad(&pow(&x, &y), &[x.clone()], &Tensor::ones(vec![2], DType::F32), CREATE_GRAPH).unwrap().remove(0);
+        let ggx = grad(&gx, &[x.clone()], &Tensor::ones(vec![2], DType::F32), BackwardOptions::default()).unwrap().remove(0);
+        assert_eq!(ggx.to_vec_f32().unwrap(), vec![0.0, 12.0]);
+    }
+
+    #[test]
+    fn test_gradcheck_batched_matmul() {
+        // Broadcast batch dims on both sides: gradients are summed back over them
+        let inputs = [rand_leaf(vec![2, 1, 3, 4], 91, -1.0, 1.0), rand_leaf(vec![3, 4, 2], 92, -1.0, 1.0)];
+        gradcheck(|x| matmul(&x[0], &x[1]), &inputs, EPS, TOL).unwrap();
+        // Attention-style scores against a transposed view
+        let qk = [rand_leaf(vec![2, 2, 3, 4], 93, -1.0, 1.0), rand_leaf(vec![2, 1, 3, 4], 94, -1.0, 1.0)];
+        gradcheck(|x| matmul(&x[0], &x[1].t()), &qk, EPS, TOL).unwrap();
+        double_gradcheck(|x| matmul(&matmul(&x[0], &x[1].t()), &x[1]), &qk);
+
+        // Shared 2-D weight over a batch of inputs
+        let mut x = Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 4.0], vec![2, 1, 2]);
+        x.requires_grad = true;
+        let mut w = Tensor::from_vec_f32(vec![1.0, 0.0, 0.0, 1.0], vec![2, 2]);
+        w.requires_grad = true;
+        backward(&sum(&matmul(&x, &w), None, false)).unwrap();
+        assert_eq!(w.grad().unwrap().shape(), &[2, 2]);
+        assert_eq!(w.grad().unwrap().to_vec_f32().unwrap(), vec![4.0, 4.0, 6.0, 6.0]);
+    }
+
+    #[test]
+    fn test_gradcheck_relu_matmul_linear() {
+        // Values away from 0 so the finite difference does not straddle the relu kink
+        let mut x = rand_leaf(vec![3, 4], 6, 0.1, 1.0);
+        let signs = Tensor::from_vec_f32((0..12).map(|i| if i % 3 == 0 { -1.0 } else { 1.0 }).collect(), vec![3, 4]);
+        x = mul(&x.detach(), &signs);
+        x.requires_grad_(true);
+        gradcheck(|x| relu(&x[0]), &[x], EPS, TOL).unwrap();
+
+        let inputs = [rand_leaf(vec![2, 3], 7, -1.0, 1.0), rand_leaf(vec![3, 4], 8, -1.0, 1.0)];
+        gradcheck(|x| matmul(&x[0], &x[1]), &inputs, EPS, TOL).unwrap();
+
+        // Linear: weight and bias as inputs, transposed weight view inside
+        let inputs = [rand_leaf(vec![2, 3], 9, -1.0, 1.0), rand_leaf(vec![4, 3], 10, -1.0, 1.0), rand_leaf(vec![4], 11, -1.0, 1.0)];
+        gradcheck(|x| {
+            let mut layer = Linear::new(3, 4, true);
+            layer.weight = x[1].clone();
+            layer.bias = Some(x[2].clone());
+            layer.forward(&x[0])
+        }, &inputs, EPS, TOL).unwrap();
+    }
+
+    #[test]
+    fn test_gradcheck_view_ops() {
+        let inputs = [rand_leaf(vec![2, 3, 2], 12, -1.0, 1.0)];
+        gradcheck(|x| x[0].reshape(&[3, 4]), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| x[0].permute(&[2, 0, 1]).reshape(&[12]), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| x[0].narrow(1, 1, 2).contiguous(), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| x[0].slice(0, 1..2).squeeze(0).unsqueeze(2), &inputs, EPS, TOL).unwrap();
+        gradcheck(|x| mul(&x[0].narrow(2, 0, 1).expand(&[2, 3, 4]), &x[0].narrow(2, 1, 1)), &inputs, EPS, TOL).unwrap();
+    }
+
+    #[derive(Debug)]
+    struct DoubledGradNode {
+        input: Tensor,
+    }
+
+    impl Node for DoubledGradNode {
+        fn parents(&self) -> Vec<Tensor> {
+            vec![self.input.clone()]
+        }
+
+        fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+            vec![mul_scalar(grad, 2.0)] // Wrong: identity should pass grad through
+        }
+    }
+
+    #[test]
+    fn test_gradcheck_detects_wrong_backward() {
+        let inputs = [rand_leaf(vec![3], 13, -1.0, 1.0)];
+        let result = gradcheck(|x| {
+            let mut out = x[0].detach();
+            out.requires_grad = true;
+            out.ctx = Some(Arc::new(DoubledGradNode { input: x[0].clone() }));
+            out
+        }, &inputs, EPS, TOL);
+        assert!(matches!(result, Err(TensorError::GradientMismatch { input: 0, .. })));
+    }
+
+    #[test]
+    fn test_export_dot() {
+        let mut x = Tensor::from_vec_f32(vec![1.0, 2.0], vec![2]);
+        x.requires_grad_(true);
+        let y = Tensor::from_vec_f32(vec![3.0, 4.0], vec![2]);
+        let z = mul(&add(&x, &y), &x);
+
+        let dot = crate::autograd::export_dot(&z);
+        assert!(dot.starts_with("digraph autograd {"));
+        assert!(dot.contains("label=\"AddNode\""));
+        assert!(dot.contains("label=\"MulNode\""));
+        assert!(dot.contains("[2] F32\\nrequires_grad=true"));
+        assert!(dot.contains("requires_grad=false"));
+        // `x` feeds two ops but is emitted once
+        assert_eq!(dot.matches(&format!("t{} [", x.id())).count(), 1);
+        assert!(dot.contains(&format!("t{} -> op{};", x.id(), z.id())));
+    }
+
+    #[test]
+    fn test_retain_grad() {
+        let mut x = Tensor::from_vec_f32(vec![1.0, 2.0], vec![2]);
+        x.requires_grad = true;
+        let h = mul_scalar(&x, 3.0);
+        let kept = add_scalar(&h, 1.0);
+        kept.retain_grad();
+        let y = mul(&kept, &h);
+        backward_with_grad(&y, &Tensor::ones(vec![2], DType::F32)).unwrap();
+
+        // Intermediates drop their gradient unless retained; leaves always keep theirs
+        assert!(h.grad().is_none());
+        assert!(y.grad().is_none());
+        assert_eq!(grad_of(&kept), vec![3.0, 6.0]);
+        assert!(kept.retains_grad() && x.retains_grad() && !h.retains_grad());
+        // d/dx (3x + 1) * 3x = 18x + 3
+        assert_eq!(grad_of(&x), vec![21.0, 39.0]);
+
+        // A second pass accumulates into stored grads without re-propagating the first pass
+        backward_with_grad(&y, &Tensor::ones(vec![2], DType::F32)).unwrap();
+        assert_eq!(grad_of(&kept), vec![6.0, 12.0]);
+        assert_eq!(grad_of(&x), vec![42.0, 78.0]);
+    }
+
+    #[test]
+    fn test_register_hook() {
+        use std::sync::Mutex;
+
+        let mut x = Tensor::from_vec_f32(vec![1.0, -2.0], vec![2]);
+        x.requires_grad = true;
+        let h = mul_scalar(&x, 10.0);
+
+        // Observe the intermediate gradient, then clip it before it reaches `x`
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        h.register_hook(move |g| {
+            log.lock().unwrap().push(g.to_vec_f32().unwrap());
+            None
+        });
+        h.register_hook(|g| {
+            let (lo, hi) = (Tensor::from_vec_f32(vec![-1.0], vec![1]), Tensor::from_vec_f32(vec![1.0], vec![1]));
+            Some(minimum(&maximum(g, &lo), &hi))
+        });
+        // Leaf hooks see the gradient before it is stored
+        x.register_hook(|g| Some(mul_scalar(g, 0.5)));
+
+        let y = mul(&h, &h); // dy/dh = 2h = [20, -40]
+        backward_with_grad(&y, &Tensor::ones(vec![2], DType::F32)).unwrap();
+
+        assert_eq!(*seen.lock().unwrap(), vec![vec![20.0, -40.0]]);
+        assert_eq!(grad_of(&x), vec![5.0, -5.0]);
+
+        // A hook changing the gradient's shape is rejected
+        let z = mul_scalar(&x, 2.0);
+        z.register_hook(|_| Some(Tensor::zeros(vec![3], DType::F32)));
+        let result = backward_with_grad(&z, &Tensor::ones(vec![2], DType::F32));
+        assert!(matches!(result, Err(TensorError::ShapeMismatch { .. })));
+    }
+
+    fn mlp_segment(x: &[Tensor]) -> Tensor {
+        relu(&add(&matmul(&x[0], &x[1].t()), &x[2]))
+    }
+
+    #[test]
+    fn test_checkpoint_matches_plain_backward() {
+        let inputs = [rand_leaf(vec![3, 4], 21, -1.0, 1.0), rand_leaf(vec![5, 4], 22, -1.0, 1.0), rand_leaf(vec![5], 23, -1.0, 1.0)];
+        let grad = rand_leaf(vec![3, 5], 24, -1.0, 1.0).detach();
+
+        let plain = mlp_segment(&inputs);
+        backward_with_grad(&plain, &grad).unwrap();
+        let expected: Vec<Vec<f32>> = inputs.iter().map(grad_of).collect();
+
+        let fresh: Vec<Tensor> = inputs.iter().map(|t| {
+            let mut leaf = t.detach();
+            leaf.requires_grad = true;
+            leaf
+        }).collect();
+        let out = checkpoint(mlp_segment, &fresh);
+        assert_eq!(out.to_vec_f32().unwrap(), plain.to_vec_f32().unwrap());
+
+        // Only the segment inputs are kept alive by the graph
+        let parents = out.ctx.as_ref().unwrap().parents();
+        assert_eq!(parents.iter().map(|p| p.id()).collect::<Vec<_>>(), fresh.iter().map(|t| t.id()).collect::<Vec<_>>());
+
+        backward_with_grad(&out, &grad).unwrap();
+        for (leaf, expected) in fresh.iter().zip(expected) {
+            assert_eq!(grad_of(leaf), expected);
+        }
+
+        gradcheck(|x| checkpoint(mlp_segment, x), &inputs, EPS, TOL).unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint_returning_input() {
+        let x = rand_leaf(vec![3], 25, -1.0, 1.0);
+        let y = checkpoint(|x| x[0].clone(), std::slice::from_ref(&x));
+        // The output gets its own identity; the input stays a leaf
+        assert_ne!(y.id(), x.id());
+        assert!(x.ctx.is_none());
+        backward(&sum(&mul(&y, &y), None, false)).unwrap();
+        assert_eq!(grad_of(&x), x.to_vec_f32().unwrap().iter().map(|v| 2.0 * v).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_checkpoint_create_graph() {
+        fn segment(x: &[Tensor]) -> Tensor {
+            tanh(&mul(&x[0], &x[1]))
+        }
+        let inputs = [rand_leaf(vec![2, 3], 26, -1.0, 1.0), rand_leaf(vec![3], 27, -1.0, 1.0)];
+        let v = rand_leaf(vec![2, 3], 28, -1.0, 1.0).detach();
+
+        let plain = grad(&segment(&inputs), &inputs, &v, CREATE_GRAPH).unwrap();
+        let checkpointed = grad(&checkpoint(segment, &inputs), &inputs, &v, CREATE_GRAPH).unwrap();
+        for (p, c) in plain.iter().zip(&checkpointed) {
+            assert!(c.requires_grad());
+            assert_eq!(p.to_vec_f32().unwrap(), c.to_vec_f32().unwrap());
+        }
+
+        double_gradcheck(|x| checkpoint(segment, x), &inputs);
+    }
+
+    #[test]
+    fn test_checkpoint_without_grad() {
+        let x = Tensor::from_vec_f32(vec![-1.0, 2.0], vec![2]);
+        let y = checkpoint(|x| relu(&x[0]), &[x]);
+        assert!(!y.requires_grad() && y.ctx.is_none());
+        assert_eq!(y.to_vec_f32().unwrap(), vec![0.0, 2.0]);
+
+        // enable_grad re-enables recording inside no_grad, and restores it on drop
+        let _guard = no_grad();
+        {
+            let _enabled = enable_grad();
+            assert!(is_grad_enabled());
+        }
+        assert!(!is_grad_enabled());
+    }
+
+    /// Custom op: `a * b + a`, computed with a raw loop like an external kernel would be.
+    struct MulAdd;
+
+    impl Function for MulAdd {
+        fn forward(&self, ctx: &mut FunctionCtx, inputs: &[Tensor]) -> Tensor {
+            ctx.save_for_backward(inputs);
+            let (a, b) = (inputs[0].to_vec_f32().unwrap(), inputs[1].to_vec_f32().unwrap());
+            let data = a.iter().zip(&b).map(|(a, b)| a * b + a).collect();
+            Tensor::from_vec_f32(data, inputs[0].shape().to_vec())
+        }
+
+        fn backward(&self, ctx: &FunctionCtx, grad: &Tensor) -> Vec<Tensor> {
+            let (a, b) = (&ctx.saved_tensors()[0], &ctx.saved_tensors()[1]);
+            let grad_b = if ctx.needs_input_grad(1) { mul(grad, a) } else { Tensor::zeros(b.shape().to_vec(), DType::F32) };
+            vec![mul(grad, &add_scalar(b, 1.0)), grad_b]
+        }
+    }
+
+    struct Identity;
+
+    impl Function for Identity {
+        fn forward(&self, _ctx: &mut FunctionCtx, inputs: &[Tensor]) -> Tensor {
+            inputs[0].clone()
+        }
+
+        fn backward(&self, _ctx: &FunctionCtx, grad: &Tensor) -> Vec<Tensor> {
+            vec![grad.clone()]
+        }
+    }
+
+    struct WrongCount;
+
+    impl Function for WrongCount {
+        fn forward(&self, _ctx: &mut FunctionCtx, inputs: &[Tensor]) -> Tensor {
+            inputs[0].clone()
+        }
+
+        fn backward(&self, _ctx: &FunctionCtx, grad: &Tensor) -> Vec<Tensor> {
+            vec![grad.clone(), grad.clone()]
+        }
+    }
+
+    struct WrongShape;
+
+    impl Function for WrongShape {
+        fn forward(&self, _ctx: &mut FunctionCtx, inputs: &[Tensor]) -> Tensor {
+            inputs[0].clone()
+        }
+
+        fn backward(&self, _ctx: &FunctionCtx, grad: &Tensor) -> Vec<Tensor> {
+            vec![grad.reshape(&[grad.numel()])]
+        }
+    }
+
+    #[test]
+    fn test_bad_function_backward_is_an_error() {
+        let x = rand_leaf(vec![2, 3], 33, -1.0, 1.0);
+        let result = backward(&sum(&WrongCount.apply(std::slice::from_ref(&x)), None, false));
+        assert!(matches!(result, Err(TensorError::GradientCount { node: "WrongCount", expected: 1, found: 2 })));
+        let result = backward(&sum(&WrongShape.apply(std::slice::from_ref(&x)), None, false));
+        assert!(matches!(result, Err(TensorError::ShapeMismatch { .. })));
+        assert!(x.grad().is_none());
+    }
+
+    #[test]
+    fn test_custom_function() {
+        let inputs = [rand_leaf(vec![2, 3], 31, -1.0, 1.0), rand_leaf(vec![2, 3], 32, -1.0, 1.0)];
+        gradcheck(|x| MulAdd.apply(x), &inputs, EPS, TOL).unwrap();
+
+        let mut a = Tensor::from_vec_f32(vec![2.0], vec![1]);
+        a.requires_grad = true;
+        let b = Tensor::from_vec_f32(vec![5.0], vec![1]);
+        let y = MulAdd.apply(&[a.clone(), b.clone()]);
+        assert_eq!(y.ctx.as_ref().unwrap().name(), "MulAdd");
+        backward(&y).unwrap();
+        assert_eq!(grad_of(&a), vec![6.0]);
+        assert!(b.grad().is_none());
+
+        // Returning an input from forward still yields a distinct graph tensor
+        let z = Identity.apply(&[a.clone()]);
+        assert_ne!(z.id(), a.id());
+        backward(&z).unwrap();
+        assert_eq!(grad_of(&a), vec![7.0]);
+
+        let _guard = no_grad();
+        assert!(MulAdd.apply(&[a, b]).ctx.is_none());
+    }
+
+    const CREATE_GRAPH: BackwardOptions = BackwardOptions { create_graph: true };
+
+    /// Gradchecks `x -> d(f(x) . v)/dx_i` for every input `i`, i.e. the second derivatives of `f`.
+    fn double_gradcheck(f: fn(&[Tensor]) -> Tensor, inputs: &[Tensor]) {
+        for i in (0..inputs.len()).filter(|&i| inputs[i].requires_grad()) {
+            gradcheck(|x| {
+                let _guard = enable_grad();
+                let y = f(x);
+                let v = rand_leaf(y.shape().to_vec(), 99, -1.0, 1.0).detach();
+                grad(&y, &x[i..i + 1], &v, CREATE_GRAPH).unwrap().remove(0)
+            }, inputs, EPS, TOL).unwrap_or_else(|e| panic!("input {}: {}", i, e));
+        }
+    }
+
+    #[test]
+    fn test_hessian_vector_product() {
+        // f(x) = x^3: grad = 3x^2 * v, and d(grad . u)/dx = 6x * v * u
+        let mut x = Tensor::from_vec_f32(vec![1.0, -2.0], vec![2]);
+        x.requires_grad = true;
+        let v = Tensor::from_vec_f32(vec![1.0, 0.5], vec![2]);
+        let y = pow_scalar(&x, 3.0);
+
+        let g = grad(&y, &[x.clone()], &v, CREATE_GRAPH).unwrap().remove(0);
+        assert_eq!(g.to_vec_f32().unwrap(), vec![3.0, 6.0]);
+        assert!(g.requires_grad());
+        // `grad` never writes stored gradients
+        assert!(x.grad().is_none());
+
+        backward_with_grad(&g, &Tensor::ones(vec![2], DType::F32)).unwrap();
+        assert_eq!(grad_of(&x), vec![6.0, -6.0]);
+
+        // Without create_graph the backward pass records nothing
+        let g = grad(&y, &[x.clone()], &v, BackwardOptions::default()).unwrap().remove(0);
+        assert!(!g.requires_grad() && g.ctx.is_none());
+    }
+
+    #[test]
+    fn test_create_graph_leaf_grad() {
+        // Gradient penalty: x.grad from a create_graph pass is itself differentiable
+        let mut x = Tensor::from_vec_f32(vec![2.0], vec![1]);
+        x.requires_grad = true;
+        let y = mul(&x, &x);
+        backward_with_options(&y, &Tensor::ones(vec![1], DType::F32), CREATE_GRAPH).unwrap();
+        let gx = x.grad().unwrap();
+        assert!(gx.requires_grad());
+        assert_eq!(gx.to_vec_f32().unwrap(), vec![4.0]);
+
+        // A later plain pass sums into a copy instead of mutating the recorded gradient
+        backward(&y).unwrap();
+        assert_eq!(grad_of(&x), vec![8.0]);
+        assert_eq!(gx.to_vec_f32().unwrap(), vec![4.0]);
+
+        // d(x.grad)/dx = 2, from the recorded graph
+        let h = grad(&gx, &[x.clone()], &Tensor::ones(vec![1], DType::F32), BackwardOptions::default()).unwrap();
+        assert_eq!(h[0].to_vec_f32().unwrap(), vec![2.0]);
+
+        let c = Tensor::from_vec_f32(vec![1.0], vec![1]);
+        assert!(matches!(grad(&y, &[c], &Tensor::ones(vec![1], DType::F32), BackwardOptions::default()), Err(TensorError::NoGraph)));
+    }
+
+    #[test]
+    fn test_double_backward_ops() {
+        double_gradcheck(|x| mul(&x[0], &x[1]), &[rand_leaf(vec![2, 3], 41, -1.0, 1.0), rand_leaf(vec![3], 42, -1.0, 1.0)]);
+        double_gradcheck(|x| div(&x[0], &x[1]), &[rand_leaf(vec![2, 3], 43, -1.0, 1.0), rand_leaf(vec![2, 3], 44, 0.5, 2.0)]);
+        double_gradcheck(|x| pow(&x[0], &x[1]), &[rand_leaf(vec![4], 45, 0.5, 2.0), rand_leaf(vec![4], 46, 0.5, 2.0).detach()]);
+        double_gradcheck(|x| pow_scalar(&x[0], 3.0), &[rand_leaf(vec![4], 47, -1.0, 1.0)]);
+        double_gradcheck(|x| mul(&add(&x[0], &x[1]), &x[0]), &[rand_leaf(vec![2, 3], 48, -1.0, 1.0), rand_leaf(vec![1, 3], 49, -1.0, 1.0)]);
+        double_gradcheck(|x| matmul(&matmul(&x[0], &x[1]), &x[1].t()), &[rand_leaf(vec![2, 3], 50, -1.0, 1.0), rand_leaf(vec![3, 2], 51, -1.0, 1.0)]);
+        double_gradcheck(|x| mul(&x[0].narrow(0, 1, 2), &x[0].narrow(0, 0, 2)), &[rand_leaf(vec![3, 2], 52, -1.0, 1.0)]);
+        double_gradcheck(|x| mul(&x[0].expand(&[2, 3]), &x[1]), &[rand_leaf(vec![1, 3], 53, -1.0, 1.0), rand_leaf(vec![2, 3], 57, -1.0, 1.0)]);
+        // Two-layer MLP: relu's mask is constant, but grads still flow between layers
+        double_gradcheck(
+            |x| matmul(&relu(&matmul(&x[0], &x[1])), &x[2]),
+            &[rand_leaf(vec![2, 3], 54, -1.0, 1.0), rand_leaf(vec![3, 4], 55, -1.0, 1.0), rand_leaf(vec![4, 2], 56, -1.0, 1.0)],
+        );
+    }
+
+    #[test]
+    fn test_gradcheck_reductions() {
+        let x = [rand_leaf(vec![2, 3, 4], 61, -1.0, 1.0)];
+        // Distinct values spaced well beyond EPS, so max/min never switch under perturbation
+        let mut spread = Tensor::from_vec_f32((0..24).map(|i| ((i * 7) % 24) as f32 * 0.1 - 1.2).collect(), vec![2, 3, 4]);
+        spread.requires_grad = true;
+        let spread = [spread];
+        for dim in [None, Some(0), Some(1), Some(2)] {
+            for keepdim in [false, true] {
+                gradcheck(|x| sum(&x[0], dim, keepdim), &x, EPS, TOL).unwrap();
+                gradcheck(|x| mean(&x[0], dim, keepdim), &x, EPS, TOL).unwrap();
+                gradcheck(|x| max(&x[0], dim, keepdim), &spread, EPS, TOL).unwrap();
+                gradcheck(|x| min(&x[0].t(), dim, keepdim), &spread, EPS, TOL).unwrap();
+                gradcheck(|x| prod(&x[0], dim, keepdim), &x, EPS, TOL).unwrap();
+            }
+        }
+
+        // prod stays exact with zeros in the lane
+        let mut z = Tensor::from_vec_f32(vec![0.0, 2.0, 3.0, 0.0, 0.0, 5.0], vec![2, 3]);
+        z.requires_grad = true;
+        backward(&sum(&prod(&z, Some(1), false), None, false)).unwrap();
+        assert_eq!(grad_of(&z), vec![6.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+
+        // Second derivatives through prod keep the cross terms, also at zeros
+        double_gradcheck(|x| prod(&x[0], Some(1), false), &[rand_leaf(vec![2, 3], 63, -1.0, 1.0)]);
+        double_gradcheck(|x| prod(&x[0].t(), None, true), &[rand_leaf(vec![2, 2], 64, -1.0, 1.0)]);
+        let mut z = Tensor::from_vec_f32(vec![0.0, 2.0, 3.0], vec![3]);
+        z.requires_grad = true;
+        let g = grad(&prod(&z, None, false), &[z.clone()], &Tensor::ones(vec![], DType::F32), CREATE_GRAPH).unwrap().remove(0);
+        backward(&sum(&g, None, false)).unwrap();
+        // d/dz of (z1 z2 + z0 z2 + z0 z1) = (z1 + z2, z0 + z2, z0 + z1)
+        assert_eq!(grad_of(&z), vec![5.0, 3.0, 2.0]);
+
+        // Ties in max split the gradient
+        let mut t = Tensor::from_vec_f32(vec![1.0, 3.0, 3.0], vec![3]);
+        t.requires_grad = true;
+        backward(&max(&t, None, false)).unwrap();
+        assert_eq!(grad_of(&t), vec![0.0, 0.5, 0.5]);
+
+        // Reductions give a scalar loss and support double backward
+        double_gradcheck(|x| mean(&mul(&x[0], &x[0]), Some(1), false), &[rand_leaf(vec![2, 3], 62, -1.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_gradcheck_softmax() {
+        let x = [rand_leaf(vec![2, 3, 4], 71, -2.0, 2.0)];
+        for dim in 0..3 {
+            gradcheck(|x| softmax(&x[0], dim), &x, EPS, TOL).unwrap();
+            gradcheck(|x| log_softmax(&x[0], dim), &x, EPS, TOL).unwrap();
+            gradcheck(|x| softmax(&x[0].t(), dim), &x, EPS, TOL).unwrap();
+        }
+
+        // The recorded backward (create_graph) agrees with the fused kernel
+        let y = rand_leaf(vec![3, 5], 72, -2.0, 2.0);
+        let v = rand_leaf(vec![3, 5], 73, -1.0, 1.0).detach();
+        for log in [false, true] {
+            let f = |t: &Tensor| if log { log_softmax(t, 1) } else { softmax(t, 1) };
+            let fused = grad(&f(&y), std::slice::from_ref(&y), &v, BackwardOptions::default()).unwrap().remove(0);
+            let recorded = grad(&f(&y), std::slice::from_ref(&y), &v, CREATE_GRAPH).unwrap().remove(0);
+            for (a, b) in fused.to_vec_f32().unwrap().iter().zip(recorded.to_vec_f32().unwrap()) {
+                assert!((a - b).abs() < 1e-6);
+            }
+        }
+
+        // Second derivatives, including attention-style softmax(q k^T) v
+        double_gradcheck(|x| softmax(&x[0], 1), std::slice::from_ref(&y));
+        double_gradcheck(|x| log_softmax(&x[0], 0), std::slice::from_ref(&y));
+        double_gradcheck(|x| softmax(&x[0].t(), 1), std::slice::from_ref(&y));
+        double_gradcheck(
+            |x| matmul(&softmax(&matmul(&x[0], &x[1].t()), 1), &x[1]),
+            &[rand_leaf(vec![2, 3], 74, -1.0, 1.0), rand_leaf(vec![4, 3], 75, -1.0, 1.0)],
+        );
+    }
+
+    #[test]
+    fn test_gradcheck_activations() {
+        // Kinks (leaky_relu at 0) are avoided by drawing magnitudes away from zero
+        let x = [rand_leaf(vec![3, 4], 81, -3.0, 3.0)];
+        for f in [sigmoid, tanh, silu, gelu, gelu_tanh] {
+            gradcheck(|x| f(&x[0]), &x, EPS, TOL).unwrap();
+            gradcheck(|x| f(&x[0].t()), &x, EPS, TOL).unwrap();
+        }
+        gradcheck(|x| softplus(&x[0], 2.0), &x, EPS, TOL).unwrap();
+        let away = [rand_leaf(vec![6], 82, 0.1, 2.0)];
+        gradcheck(|x| leaky_relu(&mul(&x[0], &Tensor::from_vec_f32(vec![1.0, -1.0, 1.0, -1.0, 1.0, -1.0], vec![6])), 0.2), &away, EPS, TOL).unwrap();
+
+        // Second derivatives through every activation (kinked ones away from their kink)
+        double_gradcheck(|x| sigmoid(&x[0]), &x);
+        double_gradcheck(|x| tanh(&x[0]), &x);
+        double_gradcheck(|x| silu(&x[0]), &x);
+        double_gradcheck(|x| gelu(&x[0]), &x);
+        double_gradcheck(|x| gelu_tanh(&x[0]), &x);
+        double_gradcheck(|x| softplus(&x[0], 2.0), &x);
+        double_gradcheck(|x| mul(&relu(&x[0]), &x[0]), &away);
+        double_gradcheck(|x| mul(&leaky_relu(&neg(&x[0]), 0.2), &x[0]), &away);
+        double_gradcheck(|x| mul(&abs(&x[0]), &x[0]), &away);
+        double_gradcheck(|x| mul(&clamp(&x[0], 0.05, 3.0), &x[0]), &away);
+
+        // A create_graph gradient through an activation stays in the graph, even for a
+        // constant seed, so Hessian-vector products work
+        let ones = Tensor::ones(vec![3, 4], DType::F32);
+        let g = grad(&tanh(&x[0]), std::slice::from_ref(&x[0]), &ones, CREATE_GRAPH).unwrap().remove(0);
+        assert!(g.requires_grad());
+        let h = grad(&g, std::slice::from_ref(&x[0]), &ones, BackwardOptions::default()).unwrap().remove(0);
+        let expected: Vec<f32> = x[0].to_vec_f32().unwrap().iter().map(|v| -2.0 * v.tanh() * (1.0 - v.tanh() * v.tanh())).collect();
+        for (a, b) in h.to_vec_f32().unwrap().iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-5, "{} vs {}", a, b);
+        }
+
+        // F16 activations backpropagate in F16
+        let mut h = Tensor::zeros(vec![2], DType::F16);
+        h.requires_grad = true;
+        let y = gelu(&h);
+        backward_with_grad(&y, &Tensor::ones(vec![2], DType::F16)).unwrap();
+        let g = h.grad().unwrap().to_vec::<half::f16>().unwrap();
+        assert_eq!(g[0].to_f32(), 0.5);
+    }
+
+    #[test]
+    fn test_gradcheck_math_ops() {
+        let x = [rand_leaf(vec![3, 4], 83, -2.0, 2.0)];
+        let pos = [rand_leaf(vec![3, 4], 84, 0.5, 3.0)];
+        for f in [exp, abs, neg, sin, cos] {
+            gradcheck(|x| f(&x[0]), &x, EPS, TOL).unwrap();
+            gradcheck(|x| f(&x[0].t()), &x, EPS, TOL).unwrap();
+        }
+        for f in [log, sqrt, rsqrt, reciprocal] {
+            gradcheck(|x| f(&x[0]), &pos, EPS, TOL).unwrap();
+            gradcheck(|x| f(&x[0].t()), &pos, EPS, TOL).unwrap();
+        }
+        // Clamp bounds sit at the range edges so no sample lands within EPS of a kink
+        let mut spread = Tensor::from_vec_f32(vec![-1.5, -0.6, 0.2, 0.9, 1.7, -0.1], vec![6]);
+        spread.requires_grad = true;
+        gradcheck(|x| clamp(&x[0], -1.0, 1.0), &[spread], EPS, TOL).unwrap();
+
+        // Composed derivatives stay differentiable
+        double_gradcheck(|x| exp(&x[0]), &x);
+        double_gradcheck(|x| sin(&x[0]), &x);
+        double_gradcheck(|x| cos(&x[0]), &x);
+        double_gradcheck(|x| mul(&neg(&x[0]), &x[0]), &x);
+        double_gradcheck(|x| log(&x[0]), &pos);
+        double_gradcheck(|x| sqrt(&x[0]), &pos);
+        double_gradcheck(|x| rsqrt(&x[0]), &pos);
+        double_gradcheck(|x| reciprocal(&x[0]), &pos);
     }
 }
@@ -0,0 +1,47 @@
+use thiserror::Error;
+use crate::tensor::{DType, Shape};
+
+/// Errors reported by the engine's fallible APIs.
+#[derive(Debug, Error)]
+pub enum TensorError {
+    #[error("dtype mismatch: expected {expected:?}, found {found:?}")]
+    DTypeMismatch { expected: DType, found: DType },
+
+    #[error("index {index:?} is out of bounds for shape {shape:?}")]
+    OutOfBounds { index: Vec<usize>, shape: Shape },
+
+    #[error("shape mismatch: expected {expected:?}, found {found:?}")]
+    ShapeMismatch { expected: Shape, found: Shape },
+
+    #[error("shapes {lhs:?} and {rhs:?} are not broadcastable")]
+    IncompatibleShapes { lhs: Shape, rhs: Shape },
+
+    #[error("operation requires a contiguous tensor")]
+    NotContiguous,
+
+    #[error("tensor does not require grad and is not part of a graph")]
+    NoGraph,
+
+    #[error("gradient mismatch for input {input} at {index:?}: analytic {analytic}, numerical {numerical}")]
+    GradientMismatch { input: usize, index: Vec<usize>, analytic: f32, numerical: f32 },
+
+    #[error("{node} returned {found} gradients for {expected} inputs")]
+    GradientCount { node: &'static str, expected: usize, found: usize },
+
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("null pointer passed for `{0}`")]
+    NullPointer(&'static str),
+
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to build thread pool: {0}")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+}
+
+pub type Result<T> = std::result::Result<T, TensorError>;
@@ -1,51 +1,153 @@
 use crate::tensor::{Tensor, Shape, DType};
-use crate::ops::matmul::{matmul, matmul_int4};
+use crate::ops::matmul::{try_matmul, try_matmul_int4};
 use crate::autograd::backward;
+use crate::parallel::set_num_threads;
+use crate::error::{Result, TensorError};
 
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::slice;
 
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message).unwrap_or_else(|_| CString::new("invalid error message").unwrap());
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+// Unwinding across the FFI boundary is undefined behavior, so every entry point runs its
+// checks and work inside `catch`: errors and panics are stored for `tensor_last_error` and
+// the entry point returns null (or -1).
+fn catch<T>(f: impl FnOnce() -> Result<T>) -> Option<T> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(e)) => {
+            set_last_error(e.to_string());
+            None
+        }
+        Err(panic) => {
+            set_last_error(panic_message(panic));
+            None
+        }
+    }
+}
+
+fn guard(f: impl FnOnce() -> Result<Tensor>) -> *mut Tensor {
+    catch(f).map_or(std::ptr::null_mut(), |tensor| Box::into_raw(Box::new(tensor)))
+}
+
+/// Like `guard` for entry points reporting a status: 0 on success, -1 on failure.
+fn guard_status(f: impl FnOnce() -> Result<()>) -> i32 {
+    catch(f).map_or(-1, |()| 0)
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    panic
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+        .unwrap_or_else(|| "panic in tensor engine".to_string())
+}
+
+/// Borrows the tensor behind a handle, rejecting null.
+unsafe fn handle<'a>(ptr: *const Tensor, name: &'static str) -> Result<&'a Tensor> {
+    ptr.as_ref().ok_or(TensorError::NullPointer(name))
+}
+
+/// Message of the last error raised on this thread, or null. Valid until the next failing call.
+#[no_mangle]
+pub extern "C" fn tensor_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(std::ptr::null(), |m| m.as_ptr()))
+}
+
+/// Caps the engine to `n` worker threads (0 restores one per core).
+/// Returns 0 on success, -1 on failure (see `tensor_last_error`).
+#[no_mangle]
+pub extern "C" fn tensor_set_num_threads(n: usize) -> i32 {
+    guard_status(|| set_num_threads(n))
+}
+
 // Helper to convert C array to Shape
-unsafe fn c_shape_to_vec(shape_ptr: *const i64, ndim: usize) -> Shape {
+unsafe fn c_shape_to_vec(shape_ptr: *const i64, ndim: usize) -> Result<Shape> {
+    if ndim == 0 {
+        return Ok(Vec::new());
+    }
+    if shape_ptr.is_null() {
+        return Err(TensorError::NullPointer("shape"));
+    }
     let slice = slice::from_raw_parts(shape_ptr, ndim);
-    slice.iter().map(|&x| x as usize).collect()
+    slice
+        .iter()
+        .map(|&x| usize::try_from(x).map_err(|_| TensorError::InvalidArgument(format!("negative dimension {} in shape {:?}", x, slice))))
+        .collect()
+}
+
+/// Element count of `shape`, rejecting shapes whose element or byte count overflows.
+fn checked_numel(shape: &[usize], dtype: DType) -> Result<usize> {
+    let overflow = || TensorError::InvalidArgument(format!("shape {:?} of {:?} is too large", shape, dtype));
+    let numel = shape.iter().try_fold(1usize, |n, &d| n.checked_mul(d)).ok_or_else(overflow)?;
+    match numel.checked_mul(dtype.size_of()) {
+        Some(bytes) if bytes <= isize::MAX as usize => Ok(numel),
+        _ => Err(overflow()),
+    }
 }
 
 // --- Creation & Destruction ---
 
+/// Copies `data` into a new F32 tensor of the given shape.
+///
+/// # Safety
+/// `shape_ptr` must point to `ndim` readable values (it may be null when `ndim` is 0), and
+/// `data` to as many `f32`s as the shape has elements.
 #[no_mangle]
-pub extern "C" fn tensor_create_f32(data: *const f32, shape_ptr: *const i64, ndim: usize) -> *mut Tensor {
-    // Catch unwinds? Rust FFI should catch panics, otherwise undefined behavior on unwind across FFI.
-    // For prototype, we skip `std::panic::catch_unwind` but in prod usage it IS MANDATORY.
-    
-    let shape = unsafe { c_shape_to_vec(shape_ptr, ndim) };
-    let numel = shape.iter().product();
-    let mut data_vec = Vec::with_capacity(numel);
-    unsafe {
-        std::ptr::copy_nonoverlapping(data, data_vec.as_mut_ptr(), numel);
-        data_vec.set_len(numel);
-    }
-    
-    let tensor = Tensor::from_vec_f32(data_vec, shape);
-    Box::into_raw(Box::new(tensor))
+pub unsafe extern "C" fn tensor_create_f32(data: *const f32, shape_ptr: *const i64, ndim: usize) -> *mut Tensor {
+    guard(|| {
+        let shape = unsafe { c_shape_to_vec(shape_ptr, ndim)? };
+        let numel = checked_numel(&shape, DType::F32)?;
+        if data.is_null() && numel > 0 {
+            return Err(TensorError::NullPointer("data"));
+        }
+        let mut data_vec = Vec::with_capacity(numel);
+        unsafe {
+            std::ptr::copy_nonoverlapping(data, data_vec.as_mut_ptr(), numel);
+            data_vec.set_len(numel);
+        }
+
+        Ok(Tensor::from_vec_f32(data_vec, shape))
+    })
 }
 
+/// New zero-filled tensor. `dtype_code`: 0 F32, 1 F16, 2 I8, 3 I4, 4 I64.
+///
+/// # Safety
+/// `shape_ptr` must point to `ndim` readable values (it may be null when `ndim` is 0).
 #[no_mangle]
-pub extern "C" fn tensor_zeros(shape_ptr: *const i64, ndim: usize, dtype_code: i32) -> *mut Tensor {
-    let shape = unsafe { c_shape_to_vec(shape_ptr, ndim) };
-    let dtype = match dtype_code {
-        0 => DType::F32,
-        1 => DType::F16,
-        2 => DType::I8, // Used for INT4 packing container?
-        3 => DType::I4,
-        _ => DType::F32, // Default
-    };
-    
-    let tensor = Tensor::zeros(shape, dtype);
-    Box::into_raw(Box::new(tensor))
+pub unsafe extern "C" fn tensor_zeros(shape_ptr: *const i64, ndim: usize, dtype_code: i32) -> *mut Tensor {
+    guard(|| {
+        let shape = unsafe { c_shape_to_vec(shape_ptr, ndim)? };
+        let dtype = match dtype_code {
+            0 => DType::F32,
+            1 => DType::F16,
+            2 => DType::I8, // Used for INT4 packing container?
+            3 => DType::I4,
+            4 => DType::I64,
+            code => return Err(TensorError::InvalidArgument(format!("unknown dtype code {}", code))),
+        };
+        checked_numel(&shape, dtype)?;
+        Ok(Tensor::zeros(shape, dtype))
+    })
 }
 
+/// Releases a handle returned by this library. Null is ignored.
+///
+/// # Safety
+/// `ptr` must be null or a handle from this library that has not been freed yet.
 #[no_mangle]
-pub extern "C" fn tensor_free(ptr: *mut Tensor) {
+pub unsafe extern "C" fn tensor_free(ptr: *mut Tensor) {
     if ptr.is_null() { return; }
     unsafe {
         let _ = Box::from_raw(ptr); // Drop happens here
@@ -54,76 +156,113 @@ pub extern "C" fn tensor_free(ptr: *mut Tensor) {
 
 // --- Operations ---
 
+/// # Safety
+/// Both handles must be null or live handles from this library.
 #[no_mangle]
-pub extern "C" fn tensor_matmul(lhs: *const Tensor, rhs: *const Tensor) -> *mut Tensor {
-    assert!(!lhs.is_null() && !rhs.is_null());
-    let lhs = unsafe { &*lhs };
-    let rhs = unsafe { &*rhs };
-    
-    let result = matmul(lhs, rhs);
-    Box::into_raw(Box::new(result))
+pub unsafe extern "C" fn tensor_matmul(lhs: *const Tensor, rhs: *const Tensor) -> *mut Tensor {
+    guard(|| {
+        let lhs = unsafe { handle(lhs, "lhs")? };
+        let rhs = unsafe { handle(rhs, "rhs")? };
+        try_matmul(lhs, rhs)
+    })
 }
 
+/// # Safety
+/// Every handle must be null or a live handle from this library.
 #[no_mangle]
-pub extern "C" fn tensor_linear_int4(
+pub unsafe extern "C" fn tensor_linear_int4(
     input: *const Tensor, 
     weight_packed: *const Tensor, 
     scales: *const Tensor,
     bias: *const Tensor // Optional, can be null
 ) -> *mut Tensor {
-    let input = unsafe { &*input };
-    let w = unsafe { &*weight_packed };
-    let s = unsafe { &*scales };
-    let b = if bias.is_null() { None } else { Some(unsafe { (&*bias).clone() }) }; // Clone ref/struct, storage shared
-    
-    // Convert bias Option<&Tensor> to Option<Tensor> for the function if needed, 
-    // or change function signature. My `matmul_int4` expected `&Option<Tensor>`.
-    // Wait, `matmul_int4` signature: `bias: &Option<Tensor>`.
-    
-    let result = matmul_int4(input, w, s, &b);
-    Box::into_raw(Box::new(result))
+    guard(|| {
+        let input = unsafe { handle(input, "input")? };
+        let w = unsafe { handle(weight_packed, "weight_packed")? };
+        let s = unsafe { handle(scales, "scales")? };
+        let b = unsafe { bias.as_ref() }.cloned(); // Clone ref/struct, storage shared
+        try_matmul_int4(input, w, s, &b)
+    })
 }
 
 // --- Autograd ---
 
+/// Returns 0 on success, -1 on failure (see `tensor_last_error`).
+///
+/// # Safety
+/// `root` must be null or a live handle from this library.
 #[no_mangle]
-pub extern "C" fn tensor_backward(root: *const Tensor) {
-    assert!(!root.is_null());
-    let root = unsafe { &*root };
-    backward(root);
+pub unsafe extern "C" fn tensor_backward(root: *const Tensor) -> i32 {
+    guard_status(|| backward(unsafe { handle(root, "root")? }))
 }
 
+/// Returns a new handle to the gradient (sharing its storage), or null if the tensor has none
+/// or on failure (see `tensor_last_error`).
+///
+/// # Safety
+/// `tensor` must be null or a live handle from this library.
 #[no_mangle]
-pub extern "C" fn tensor_grad(tensor: *const Tensor) -> *mut Tensor {
-    assert!(!tensor.is_null());
-    let t = unsafe { &*tensor };
-    
-    // Return gradient tensor if exists.
-    // If we just want to peek, we can return a copy (view).
-    // The `grad` field is `Arc<RwLock<Option<Tensor>>>`.
-    
-    let lock = t.grad.read().unwrap();
-    if let Some(g) = lock.as_ref() {
-        // Return a clone (new Tensor struct pointing to same storage)
-        Box::into_raw(Box::new(g.clone()))
-    } else {
-        std::ptr::null_mut()
-    }
+pub unsafe extern "C" fn tensor_grad(tensor: *const Tensor) -> *mut Tensor {
+    catch(|| {
+        let t = unsafe { handle(tensor, "tensor")? };
+        Ok(t.grad.read().unwrap().clone())
+    })
+    .flatten()
+    .map_or(std::ptr::null_mut(), |g| Box::into_raw(Box::new(g)))
 }
 
 // --- Accessors ---
 
+/// Borrowed view of the data, only valid while the tensor is alive and not written to.
+/// Null unless the tensor is a contiguous F32 tensor; use `tensor_copy_to_f32` otherwise.
+///
+/// # Safety
+/// `tensor` must be null or a live handle from this library.
+#[no_mangle]
+pub unsafe extern "C" fn tensor_data_ptr(tensor: *const Tensor) -> *const f32 {
+    catch(|| {
+        let t = unsafe { handle(tensor, "tensor")? };
+        Ok(t.as_slice::<f32>()?.as_ptr())
+    })
+    .unwrap_or(std::ptr::null())
+}
+
+/// Copies the elements of an F32 tensor in row-major order into `out` (capacity `len`).
+/// Works for any view. Returns the number of elements written, or -1 on failure (see
+/// `tensor_last_error`).
+///
+/// # Safety
+/// `tensor` must be null or a live handle from this library, and `out` must be null or
+/// writable for `len` values.
 #[no_mangle]
-pub extern "C" fn tensor_data_ptr(tensor: *const Tensor) -> *const f32 {
-    let t = unsafe { &*tensor };
-    // Assuming F32 for now.
-    // Unsafe access, only valid while Tensor is alive.
-    t.storage.as_ptr() as *const f32
+pub unsafe extern "C" fn tensor_copy_to_f32(tensor: *const Tensor, out: *mut f32, len: usize) -> i64 {
+    catch(|| {
+        let t = unsafe { handle(tensor, "tensor")? };
+        let data = t.to_vec_f32()?;
+        if data.len() > len {
+            return Err(TensorError::OutOfBounds { index: vec![data.len()], shape: vec![len] });
+        }
+        if out.is_null() && !data.is_empty() {
+            return Err(TensorError::NullPointer("out"));
+        }
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), out, data.len()) };
+        Ok(data.len() as i64)
+    })
+    .unwrap_or(-1)
 }
 
+/// Returns a pointer to the shape (valid while the tensor is alive) and stores its length in
+/// `out_ndim`, or null on failure (see `tensor_last_error`).
+///
+/// # Safety
+/// `tensor` must be null or a live handle from this library, and `out_ndim` null or writable.
 #[no_mangle]
-pub extern "C" fn tensor_get_shape(tensor: *const Tensor, out_ndim: *mut usize) -> *const usize {
-    let t = unsafe { &*tensor };
-    unsafe { *out_ndim = t.shape.len(); }
-    t.shape.as_ptr()
+pub unsafe extern "C" fn tensor_get_shape(tensor: *const Tensor, out_ndim: *mut usize) -> *const usize {
+    catch(|| {
+        let t = unsafe { handle(tensor, "tensor")? };
+        let out_ndim = unsafe { out_ndim.as_mut() }.ok_or(TensorError::NullPointer("out_ndim"))?;
+        *out_ndim = t.shape.len();
+        Ok(t.shape.as_ptr())
+    })
+    .unwrap_or(std::ptr::null())
 }
@@ -1 +1,5 @@
 pub mod c_abi;
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+pub mod tests;
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::ffi::c_abi::*;
+    use crate::tensor::Tensor;
+    use std::ffi::CStr;
+
+    fn last_error() -> String {
+        let message = tensor_last_error();
+        assert!(!message.is_null());
+        unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_null_handles_report_errors() {
+        unsafe {
+            let null = std::ptr::null::<Tensor>();
+            let t = tensor_zeros([2i64, 2].as_ptr(), 2, 0);
+            assert!(!t.is_null());
+
+            assert!(tensor_matmul(null, t).is_null());
+            assert!(last_error().contains("`lhs`"));
+            assert!(tensor_linear_int4(t, t, null, null).is_null());
+            assert!(last_error().contains("`scales`"));
+            assert_eq!(tensor_backward(null), -1);
+            assert!(last_error().contains("`root`"));
+            assert!(tensor_grad(null).is_null());
+            assert!(tensor_data_ptr(null).is_null());
+            assert!(tensor_get_shape(t, std::ptr::null_mut()).is_null());
+            assert!(last_error().contains("`out_ndim`"));
+            assert!(tensor_create_f32(std::ptr::null(), [3i64].as_ptr(), 1).is_null());
+            assert!(last_error().contains("`data`"));
+
+            // Size mismatches are reported rather than silently truncated
+            let mut out = [0.0f32; 3];
+            assert_eq!(tensor_copy_to_f32(null, out.as_mut_ptr(), 3), -1);
+            assert_eq!(tensor_copy_to_f32(t, out.as_mut_ptr(), 3), -1);
+            assert!(last_error().contains("out of bounds"));
+
+            // Errors from the engine itself come through the same path
+            let v = tensor_zeros([3i64].as_ptr(), 1, 0);
+            assert!(tensor_matmul(t, v).is_null());
+            assert!(!last_error().is_empty());
+
+            tensor_free(t);
+            tensor_free(v);
+        }
+    }
+
+    #[test]
+    fn test_invalid_shapes_report_errors() {
+        unsafe {
+            // -1 x -1 would wrap to a one-element buffer claiming a huge shape
+            let data = [1.0f32];
+            assert!(tensor_create_f32(data.as_ptr(), [-1i64, -1].as_ptr(), 2).is_null());
+            assert!(last_error().contains("negative dimension"));
+            assert!(tensor_zeros([2i64, -3].as_ptr(), 2, 0).is_null());
+            assert!(last_error().contains("negative dimension"));
+
+            // Element and byte counts are checked for overflow
+            assert!(tensor_zeros([1i64 << 32, 1 << 32].as_ptr(), 2, 0).is_null());
+            assert!(last_error().contains("too large"));
+            assert!(tensor_zeros([1i64 << 61].as_ptr(), 1, 4).is_null());
+            assert!(last_error().contains("too large"));
+            assert!(tensor_create_f32(data.as_ptr(), [i64::MAX, 2].as_ptr(), 2).is_null());
+            assert!(last_error().contains("too large"));
+
+            // Unknown dtype codes are rejected rather than read as F32
+            assert!(tensor_zeros([2i64].as_ptr(), 1, 7).is_null());
+            assert!(last_error().contains("unknown dtype code 7"));
+            assert!(tensor_zeros([2i64].as_ptr(), 1, -1).is_null());
+        }
+    }
+}
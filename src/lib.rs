@@ -1,9 +1,13 @@
 pub mod autograd;
+pub mod error;
 pub mod ffi;
 pub mod nn;
 pub mod ops;
+pub mod parallel;
 pub mod tensor;
 
+pub use error::TensorError;
+pub use parallel::{num_threads, set_num_threads, with_thread_pool};
 pub use tensor::Tensor;
 
 #[cfg(test)]
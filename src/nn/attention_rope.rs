@@ -11,6 +11,10 @@ pub fn rope(x: &Tensor, freqs_cos: &Tensor, freqs_sin: &Tensor) -> Tensor {
     // x_out[2i+1] = x[2i] * sin[i] + x[2i+1] * cos[i]
     
     assert_eq!(x.dtype, DType::F32);
+    // The pairwise loop below walks flat memory
+    let x = x.contiguous();
+    let freqs_cos = freqs_cos.contiguous();
+    let freqs_sin = freqs_sin.contiguous();
     let output = Tensor::zeros(x.shape.clone(), x.dtype);
     
     // Unsafe fast path
@@ -1,4 +1,5 @@
 use crate::tensor::{Tensor, DType};
+use crate::error::{Result, TensorError};
 
 pub struct KVCache {
     pub k: Tensor, // [MaxSeq, Head, Dim]
@@ -19,45 +20,48 @@ impl KVCache {
         }
     }
     
-    pub fn update(&mut self, new_k: &Tensor, new_v: &Tensor, pos: usize) {
+    /// Writes `new_k` / `new_v` (`[Len, Head, Dim]`) at positions `pos..pos + Len`.
+    /// Both are validated before anything is written, so on error the cache is unchanged.
+    pub fn try_update(&mut self, new_k: &Tensor, new_v: &Tensor, pos: usize) -> Result<()> {
         // new_k: [1, Head, Dim] (single token update) or [Len, Head, Dim]
-        // Copy new_k into self.k at slice [pos..pos+len]
-        
-        // This requires `copy_from` or slice update method in Tensor.
-        // Using unsafe ptr copy for now.
-        
+        let len = new_k.shape.first().copied().unwrap_or(0);
+        let expected = vec![len, self.k.shape[1], self.k.shape[2]];
+        for new in [new_k, new_v] {
+            if new.dtype != self.k.dtype {
+                return Err(TensorError::DTypeMismatch { expected: self.k.dtype, found: new.dtype });
+            }
+            if new.shape != expected {
+                return Err(TensorError::ShapeMismatch { expected, found: new.shape.clone() });
+            }
+        }
+
+        // Bounds check: KV Cache overflow
+        if pos + len > self.max_seq_len {
+            return Err(TensorError::OutOfBounds { index: vec![pos + len], shape: vec![self.max_seq_len] });
+        }
+
+        // Views from `get_view` still alive keep reading the old contents
+        self.k.make_unique();
+        self.v.make_unique();
+
+        // Strided copies into the cache window, so new_k / new_v may be any view.
+        // The cache now holds the only handles to its storage, and both copies were validated above.
         unsafe {
-             let k_dst = self.k.storage.as_ptr() as *mut f32;
-             let v_dst = self.v.storage.as_ptr() as *mut f32;
-             
-             let k_src = new_k.storage.as_ptr().add(new_k.offset) as *const f32;
-             let v_src = new_v.storage.as_ptr().add(new_v.offset) as *const f32;
-             
-             // Stride math needed here locally.
-             // Assume contiguous [Seq, Head, Dim] layout for cache.
-             // pos corresponds to the first dimension index.
-             let stride_0 = self.k.strides[0]; 
-             let num_to_copy = new_k.numel(); // Assume dense update
-             
-             let offset = pos * stride_0;
-             
-             // Bounds check
-             if pos + new_k.shape[0] > self.max_seq_len {
-                 panic!("KV Cache overflow");
-             }
-             
-             // This naive copy assumes `new_k` shape matches [Len, Head, Dim] flattening perfectly to the slice
-             std::ptr::copy_nonoverlapping(k_src, k_dst.add(offset), num_to_copy);
-             std::ptr::copy_nonoverlapping(v_src, v_dst.add(offset), num_to_copy);
+            self.k.narrow(0, pos, len).try_copy_from(new_k)?;
+            self.v.narrow(0, pos, len).try_copy_from(new_v)?;
         }
-        
-        self.current_pos = pos + new_k.shape[0];
+
+        self.current_pos = pos + len;
+        Ok(())
+    }
+
+    /// Panicking form of [`try_update`](Self::try_update).
+    pub fn update(&mut self, new_k: &Tensor, new_v: &Tensor, pos: usize) {
+        self.try_update(new_k, new_v, pos).unwrap_or_else(|e| panic!("{}", e))
     }
     
-    pub fn get_view(&self, _len: usize) -> (Tensor, Tensor) {
-        // Return view of k, v up to len
-        // Slicing not fully implemented in Tensor generic view yet.
-        // Returning full cache for now or manually constructing view.
-        (self.k.clone(), self.v.clone()) 
+    pub fn get_view(&self, len: usize) -> (Tensor, Tensor) {
+        // Zero-copy views of the first `len` positions: [len, Head, Dim]
+        (self.k.narrow(0, 0, len), self.v.narrow(0, 0, len))
     }
 }
@@ -1,6 +1,7 @@
 use crate::tensor::{Tensor, DType};
-use crate::ops::matmul::matmul;
-use crate::ops::binary::add;
+use crate::ops::matmul::try_matmul;
+use crate::ops::binary::try_add;
+use crate::error::Result;
 
 
 pub struct Linear {
@@ -22,30 +23,25 @@ impl Linear {
     }
 
     pub fn forward(&self, input: &Tensor) -> Tensor {
+        self.try_forward(input).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_forward(&self, input: &Tensor) -> Result<Tensor> {
         // y = x @ W.T + b
-        // x: [Batch, In]
+        // x: [..., In]
         // W: [Out, In]
         // W.T: [In, Out]
-        // y: [Batch, Out]
+        // y: [..., Out]
         
-        // Matmul expects explicit Tensors. 
-        // Our matmul is naive, expects (M, K) x (K, N)
-        // input: (Batch, In), weight.t(): (In, Out)
-        
-        // Note: Generic `t()` creates a view.
-        let out = matmul(input, &self.weight.t());
+        // Note: Generic `t()` creates a view; matmul reads it through its strides
+        // and broadcasts it over any leading dims of the input.
+        let out = try_matmul(input, &self.weight.t())?;
         
         if let Some(b) = &self.bias {
-            // Broadcasting add needed.
-            // b is [Out]. out is [Batch, Out].
-            // Current add implementation is naive elementwise same shape.
-            // Need broadcast support. Assuming Batch=1 for now or implementing naive broadcast in Add later.
-            // For now, let's just assert Batch=1 or ignore bias if shapes mismatch... 
-            // Better: implement basic broadcast in `add` logic?
-            // Or just loop here.
-            add(&out, b) // This will panic if shapes mismatch
+            // b is [Out] and broadcasts over the leading dims of out [..., Out].
+            try_add(&out, b)
         } else {
-            out
+            Ok(out)
         }
     }
 }
@@ -76,6 +72,10 @@ impl LinearInt4 {
     }
     
     pub fn forward(&self, input: &Tensor) -> Tensor {
+        self.try_forward(input).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_forward(&self, input: &Tensor) -> Result<Tensor> {
         // x: [Batch, In]
         // W: [Out, In] (Packed)
         // y: x @ W.T
@@ -85,6 +85,6 @@ impl LinearInt4 {
         // We implement a custom Op for this.
         
         // Placeholder for the kernel call
-        crate::ops::matmul::matmul_int4(input, &self.weight_packed, &self.scales, &self.bias)
+        crate::ops::matmul::try_matmul_int4(input, &self.weight_packed, &self.scales, &self.bias)
     }
 }
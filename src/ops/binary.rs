@@ -1,5 +1,9 @@
+use std::sync::Arc;
 use crate::tensor::{Tensor, DType};
 use crate::autograd::node::Node;
+use crate::autograd::grad_mode::needs_grad;
+use crate::error::{Result, TensorError};
+use crate::ops::strided::{for_each_offset, map_binary, map_unary};
 
 
 #[derive(Debug)]
@@ -15,40 +19,477 @@ impl Node for AddNode {
 
     fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
         // z = x + y -> dz/dx = 1 * grad, dz/dy = 1 * grad
-        // Need to handle broadcasting reduction!
-        // For now assuming same shape.
-        vec![grad.clone(), grad.clone()]
+        // A broadcast parent was read many times, so its gradient is the sum over the broadcast dims.
+        vec![
+            reduce_to_shape(grad, &self.lhs.shape),
+            reduce_to_shape(grad, &self.rhs.shape),
+        ]
     }
 }
 
-pub fn add(lhs: &Tensor, rhs: &Tensor) -> Tensor {
-    // Basic elementwise add (assuming same shape/strides for now)
-    assert_eq!(lhs.shape, rhs.shape, "Broadcasting not implemented yet");
-    
-    let output = Tensor::zeros(lhs.shape.clone(), lhs.dtype);
-    
-    // Unsafe fast path
-    if lhs.dtype == DType::F32 {
-         unsafe {
-             let a_ptr = lhs.storage.as_ptr().add(lhs.offset) as *const f32;
-             let b_ptr = rhs.storage.as_ptr().add(rhs.offset) as *const f32;
-             let c_ptr = output.storage.as_ptr() as *mut f32;
-             
-             for i in 0..lhs.numel() {
-                 *c_ptr.add(i) = *a_ptr.add(i) + *b_ptr.add(i);
-             }
-         }
-    }
-    
-    if lhs.requires_grad || rhs.requires_grad {
-        // output.requires_grad = true; // Mutating output inside? Tensor::zeros returns new struct.
-        // But `output` is owned mut here? No it's not.
-        // Wait, Tensor::zeros returns Self.
-        let mut out = output;
-        out.requires_grad = true;
-        out.ctx = Some(Box::new(AddNode { lhs: lhs.clone(), rhs: rhs.clone() }));
+/// Validates the operands of a broadcasting binary op.
+fn check_binary(lhs: &Tensor, rhs: &Tensor) -> Result<()> {
+    if lhs.dtype != rhs.dtype {
+        return Err(TensorError::DTypeMismatch { expected: lhs.dtype, found: rhs.dtype });
+    }
+    if lhs.dtype != DType::F32 {
+        return Err(TensorError::Unsupported(format!("elementwise ops on {:?}", lhs.dtype)));
+    }
+    if Tensor::broadcast_shape(&lhs.shape, &rhs.shape).is_none() {
+        return Err(TensorError::IncompatibleShapes { lhs: lhs.shape.clone(), rhs: rhs.shape.clone() });
+    }
+    Ok(())
+}
+
+/// Sums `grad` down to `shape`, undoing a broadcast.
+/// Leading dims are summed away and dims that were size 1 in `shape` are summed with keepdim.
+pub(crate) fn reduce_to_shape(grad: &Tensor, shape: &[usize]) -> Tensor {
+    if grad.shape == shape {
+        return grad.clone();
+    }
+
+    let output = Tensor::zeros(shape.to_vec(), grad.dtype);
+
+    if grad.dtype == DType::F32 {
+        // View the output broadcast to grad's shape: every grad element lands on the
+        // output element it was broadcast from.
+        let out_strides = output.broadcast_strides(&grad.shape);
+        unsafe {
+            let g_ptr = grad.storage.as_ptr().add(grad.offset) as *const f32;
+            let o_ptr = output.storage.as_ptr() as *mut f32;
+
+            for_each_offset(&grad.shape, [&grad.strides, &out_strides], |_, [g, o]| {
+                *o_ptr.add(o) += *g_ptr.add(g);
+            });
+        }
+    }
+
+    // Recorded so gradients built from it stay differentiable (double backward)
+    if needs_grad(&[grad]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(SumToShapeNode { input: grad.clone() }));
         return out;
     }
 
     output
 }
+
+#[derive(Debug)]
+pub struct SumToShapeNode {
+    input: Tensor,
+}
+
+impl Node for SumToShapeNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // Each input element contributed once to the output element it was summed into
+        vec![grad.expand(&self.input.shape)]
+    }
+}
+
+/// Elementwise `lhs + rhs` with broadcasting.
+pub fn try_add(lhs: &Tensor, rhs: &Tensor) -> Result<Tensor> {
+    check_binary(lhs, rhs)?;
+    let output = map_binary(lhs, rhs, |a, b| a + b);
+
+    if needs_grad(&[lhs, rhs]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(AddNode { lhs: lhs.clone(), rhs: rhs.clone() }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_add`].
+pub fn add(lhs: &Tensor, rhs: &Tensor) -> Tensor {
+    try_add(lhs, rhs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct SubNode {
+    lhs: Tensor,
+    rhs: Tensor,
+}
+
+impl Node for SubNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.lhs.clone(), self.rhs.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // z = x - y -> dz/dx = grad, dz/dy = -grad
+        vec![
+            reduce_to_shape(grad, &self.lhs.shape),
+            reduce_to_shape(&mul_scalar(grad, -1.0), &self.rhs.shape),
+        ]
+    }
+}
+
+/// Elementwise `lhs - rhs` with broadcasting.
+pub fn try_sub(lhs: &Tensor, rhs: &Tensor) -> Result<Tensor> {
+    check_binary(lhs, rhs)?;
+    let output = map_binary(lhs, rhs, |a, b| a - b);
+
+    if needs_grad(&[lhs, rhs]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(SubNode { lhs: lhs.clone(), rhs: rhs.clone() }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_sub`].
+pub fn sub(lhs: &Tensor, rhs: &Tensor) -> Tensor {
+    try_sub(lhs, rhs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct MulNode {
+    lhs: Tensor,
+    rhs: Tensor,
+}
+
+impl Node for MulNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.lhs.clone(), self.rhs.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // z = x * y -> dz/dx = grad * y, dz/dy = grad * x
+        vec![
+            reduce_to_shape(&mul(grad, &self.rhs), &self.lhs.shape),
+            reduce_to_shape(&mul(grad, &self.lhs), &self.rhs.shape),
+        ]
+    }
+}
+
+/// Elementwise `lhs * rhs` with broadcasting.
+pub fn try_mul(lhs: &Tensor, rhs: &Tensor) -> Result<Tensor> {
+    check_binary(lhs, rhs)?;
+    let output = map_binary(lhs, rhs, |a, b| a * b);
+
+    if needs_grad(&[lhs, rhs]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(MulNode { lhs: lhs.clone(), rhs: rhs.clone() }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_mul`].
+pub fn mul(lhs: &Tensor, rhs: &Tensor) -> Tensor {
+    try_mul(lhs, rhs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct DivNode {
+    lhs: Tensor,
+    rhs: Tensor,
+}
+
+impl Node for DivNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.lhs.clone(), self.rhs.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // z = x / y -> dz/dx = grad / y, dz/dy = -grad * x / y^2
+        let dlhs = div(grad, &self.rhs);
+        let drhs = mul_scalar(&div(&mul(&dlhs, &self.lhs), &self.rhs), -1.0);
+        vec![
+            reduce_to_shape(&dlhs, &self.lhs.shape),
+            reduce_to_shape(&drhs, &self.rhs.shape),
+        ]
+    }
+}
+
+/// Elementwise `lhs / rhs` with broadcasting.
+pub fn try_div(lhs: &Tensor, rhs: &Tensor) -> Result<Tensor> {
+    check_binary(lhs, rhs)?;
+    let output = map_binary(lhs, rhs, |a, b| a / b);
+
+    if needs_grad(&[lhs, rhs]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(DivNode { lhs: lhs.clone(), rhs: rhs.clone() }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_div`].
+pub fn div(lhs: &Tensor, rhs: &Tensor) -> Tensor {
+    try_div(lhs, rhs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct PowNode {
+    lhs: Tensor,
+    rhs: Tensor,
+}
+
+impl Node for PowNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.lhs.clone(), self.rhs.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // z = x ^ y -> dz/dx = grad * y * x^(y-1), dz/dy = grad * x^y * ln(x)
+        // Built from ops so d/dx stays differentiable; d/dy is treated as a constant (no `ln` op).
+        // At x == y == 0 the exponent y - 1 is bumped to 0, so dbase is 0 * 0^0 = 0 instead of
+        // 0 * 0^-1 = NaN; the bump is a constant, and the nested pow applies the same rule.
+        let bump = map_binary(&self.lhs, &self.rhs, |a, b| if a == 0.0 && b == 0.0 { 1.0 } else { 0.0 });
+        let dbase = mul(&self.rhs, &pow(&self.lhs, &add(&add_scalar(&self.rhs, -1.0), &bump)));
+        // d/dy is taken as 0 where x == 0 (x^y is flat there for y > 0), instead of 0 * -inf
+        let dexp = map_binary(&self.lhs, &self.rhs, |a, b| if a == 0.0 { 0.0 } else { a.powf(b) * a.ln() });
+        vec![
+            reduce_to_shape(&mul(grad, &dbase), &self.lhs.shape),
+            reduce_to_shape(&mul(grad, &dexp), &self.rhs.shape),
+        ]
+    }
+}
+
+/// Elementwise `lhs ^ rhs` with broadcasting.
+pub fn try_pow(lhs: &Tensor, rhs: &Tensor) -> Result<Tensor> {
+    check_binary(lhs, rhs)?;
+    let output = map_binary(lhs, rhs, f32::powf);
+
+    if needs_grad(&[lhs, rhs]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(PowNode { lhs: lhs.clone(), rhs: rhs.clone() }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_pow`].
+pub fn pow(lhs: &Tensor, rhs: &Tensor) -> Tensor {
+    try_pow(lhs, rhs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Shared node for `maximum` / `minimum`: the gradient flows to whichever input was selected.
+#[derive(Debug)]
+pub struct SelectNode {
+    lhs: Tensor,
+    rhs: Tensor,
+    is_max: bool,
+}
+
+impl Node for SelectNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.lhs.clone(), self.rhs.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // Mask is 1 where lhs won, 0 where rhs won. Ties split the gradient evenly.
+        let is_max = self.is_max;
+        let lhs_mask = map_binary(&self.lhs, &self.rhs, |a, b| {
+            if a == b { 0.5 } else if (a > b) == is_max { 1.0 } else { 0.0 }
+        });
+        let rhs_mask = map_binary(&self.lhs, &self.rhs, |a, b| {
+            if a == b { 0.5 } else if (a > b) == is_max { 0.0 } else { 1.0 }
+        });
+        vec![
+            reduce_to_shape(&mul(grad, &lhs_mask), &self.lhs.shape),
+            reduce_to_shape(&mul(grad, &rhs_mask), &self.rhs.shape),
+        ]
+    }
+}
+
+fn select(lhs: &Tensor, rhs: &Tensor, is_max: bool) -> Result<Tensor> {
+    check_binary(lhs, rhs)?;
+    let output = if is_max {
+        map_binary(lhs, rhs, f32::max)
+    } else {
+        map_binary(lhs, rhs, f32::min)
+    };
+
+    if needs_grad(&[lhs, rhs]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(SelectNode { lhs: lhs.clone(), rhs: rhs.clone(), is_max }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Elementwise maximum of two tensors.
+pub fn try_maximum(lhs: &Tensor, rhs: &Tensor) -> Result<Tensor> {
+    select(lhs, rhs, true)
+}
+
+/// Panicking form of [`try_maximum`].
+pub fn maximum(lhs: &Tensor, rhs: &Tensor) -> Tensor {
+    try_maximum(lhs, rhs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Elementwise minimum of two tensors.
+pub fn try_minimum(lhs: &Tensor, rhs: &Tensor) -> Result<Tensor> {
+    select(lhs, rhs, false)
+}
+
+/// Panicking form of [`try_minimum`].
+pub fn minimum(lhs: &Tensor, rhs: &Tensor) -> Tensor {
+    try_minimum(lhs, rhs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// --- Scalar variants ---
+
+#[derive(Debug)]
+pub struct AddScalarNode {
+    input: Tensor,
+}
+
+impl Node for AddScalarNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![grad.clone()]
+    }
+}
+
+/// Validates the operand of a scalar op; F16 is computed in F32.
+fn check_scalar(input: &Tensor) -> Result<()> {
+    if !matches!(input.dtype, DType::F32 | DType::F16) {
+        return Err(TensorError::Unsupported(format!("elementwise ops on {:?}", input.dtype)));
+    }
+    Ok(())
+}
+
+/// Elementwise `input + value`.
+pub fn try_add_scalar(input: &Tensor, value: f32) -> Result<Tensor> {
+    check_scalar(input)?;
+    let output = map_unary(input, |a| a + value);
+
+    if needs_grad(&[input]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(AddScalarNode { input: input.clone() }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_add_scalar`].
+pub fn add_scalar(input: &Tensor, value: f32) -> Tensor {
+    try_add_scalar(input, value).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Elementwise `input - value`.
+pub fn try_sub_scalar(input: &Tensor, value: f32) -> Result<Tensor> {
+    try_add_scalar(input, -value)
+}
+
+/// Panicking form of [`try_sub_scalar`].
+pub fn sub_scalar(input: &Tensor, value: f32) -> Tensor {
+    add_scalar(input, -value)
+}
+
+#[derive(Debug)]
+pub struct MulScalarNode {
+    input: Tensor,
+    value: f32,
+}
+
+impl Node for MulScalarNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![mul_scalar(grad, self.value)]
+    }
+}
+
+/// Elementwise `input * value`.
+pub fn try_mul_scalar(input: &Tensor, value: f32) -> Result<Tensor> {
+    check_scalar(input)?;
+    let output = map_unary(input, |a| a * value);
+
+    if needs_grad(&[input]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(MulScalarNode { input: input.clone(), value }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_mul_scalar`].
+pub fn mul_scalar(input: &Tensor, value: f32) -> Tensor {
+    try_mul_scalar(input, value).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Elementwise `input / value`.
+pub fn try_div_scalar(input: &Tensor, value: f32) -> Result<Tensor> {
+    try_mul_scalar(input, value.recip())
+}
+
+/// Panicking form of [`try_div_scalar`].
+pub fn div_scalar(input: &Tensor, value: f32) -> Tensor {
+    mul_scalar(input, value.recip())
+}
+
+#[derive(Debug)]
+pub struct PowScalarNode {
+    input: Tensor,
+    exponent: f32,
+}
+
+impl Node for PowScalarNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // x^0 is constant; the general form below would give 0 * 0^-1 = NaN at x == 0
+        if self.exponent == 0.0 {
+            return vec![Tensor::zeros(self.input.shape.clone(), grad.dtype)];
+        }
+        // d/dx x^n = n * x^(n-1)
+        let n = self.exponent;
+        if needs_grad(&[grad, &self.input]) {
+            return vec![mul(grad, &mul_scalar(&pow_scalar(&self.input, n - 1.0), n))];
+        }
+        // Not recorded: any dtype the scalar ops accept, F16 included
+        vec![map_binary(grad, &self.input, |g, x| g * n * x.powf(n - 1.0))]
+    }
+}
+
+/// Elementwise `input ^ exponent`.
+pub fn try_pow_scalar(input: &Tensor, exponent: f32) -> Result<Tensor> {
+    check_scalar(input)?;
+    let output = map_unary(input, |a| a.powf(exponent));
+
+    if needs_grad(&[input]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(PowScalarNode { input: input.clone(), exponent }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_pow_scalar`].
+pub fn pow_scalar(input: &Tensor, exponent: f32) -> Tensor {
+    try_pow_scalar(input, exponent).unwrap_or_else(|e| panic!("{}", e))
+}
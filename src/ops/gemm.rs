@@ -0,0 +1,234 @@
+// Cache-blocked F32 GEMM in the BLIS layout: B is packed into KC x NC panels of NR columns,
+// A into MC x KC panels of MR rows, and an MR x NR micro-kernel runs over the packed panels.
+// The AVX2 + FMA micro-kernel lives in `ops::simd`; the scalar one below is the portable path.
+// Operands are read through row/column strides, so transposed views are packed without a copy.
+
+use rayon::prelude::*;
+use crate::parallel::{install, num_threads, SyncPtr};
+
+/// Rows of C per micro-kernel tile.
+pub(crate) const MR: usize = 6;
+/// Columns of C per micro-kernel tile (two 8-wide vectors).
+pub(crate) const NR: usize = 16;
+/// Depth of a packed block; one A and one B panel of this depth stay in L1.
+const KC: usize = 256;
+/// Rows of A per packed block (L2).
+const MC: usize = 96;
+/// Columns of B per packed block (L3).
+const NC: usize = 1024;
+/// Below this many multiply-adds packing costs more than it saves.
+const SMALL_GEMM: usize = 4096;
+/// Products with at least this many multiply-adds (64^3) run on the engine's pool.
+const PARALLEL_GEMM: usize = 1 << 18;
+
+/// Read-only strided matrix: element `(i, j)` is at `ptr + i * rs + j * cs`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MatRef {
+    pub ptr: *const f32,
+    pub rs: usize,
+    pub cs: usize,
+}
+
+// Views only read from storage kept alive by the operands of the product
+unsafe impl Send for MatRef {}
+unsafe impl Sync for MatRef {}
+
+impl MatRef {
+    /// The same layout starting `elements` further into storage.
+    pub(crate) fn offset(self, elements: usize) -> MatRef {
+        MatRef { ptr: unsafe { self.ptr.add(elements) }, ..self }
+    }
+
+    /// The sub-matrix starting at `(i, j)`.
+    fn block(self, i: usize, j: usize) -> MatRef {
+        MatRef { ptr: unsafe { self.ptr.add(i * self.rs + j * self.cs) }, ..self }
+    }
+
+    #[inline(always)]
+    unsafe fn at(self, i: usize, j: usize) -> f32 {
+        *self.ptr.add(i * self.rs + j * self.cs)
+    }
+}
+
+/// Scalar reference: `C = A B` with the naive triple loop. `C` is dense with row stride `ldc`.
+///
+/// # Safety
+/// `a` must address `m x k` elements, `b` `k x n`, and `c` `m` rows of `ldc >= n`.
+pub(crate) unsafe fn gemm_reference(m: usize, n: usize, k: usize, a: MatRef, b: MatRef, c: *mut f32, ldc: usize) {
+    for i in 0..m {
+        for j in 0..n {
+            let mut sum = 0.0;
+            for p in 0..k {
+                sum += a.at(i, p) * b.at(p, j);
+            }
+            *c.add(i * ldc + j) = sum;
+        }
+    }
+}
+
+/// `C = A B` for an `m x k` by `k x n` product, overwriting `C` (dense, row stride `ldc`).
+///
+/// Large products split each packed block into tasks of (MC rows x a group of NR panels) on
+/// the engine's pool. Every element of C is owned by one task and summed in the same order,
+/// so results do not depend on the thread count.
+///
+/// # Safety
+/// Same contract as [`gemm_reference`].
+pub(crate) unsafe fn gemm(m: usize, n: usize, k: usize, a: MatRef, b: MatRef, c: *mut f32, ldc: usize) {
+    if m * n * k <= SMALL_GEMM {
+        return gemm_reference(m, n, k, a, b, c, ldc);
+    }
+    if k == 0 {
+        for i in 0..m {
+            std::ptr::write_bytes(c.add(i * ldc), 0, n);
+        }
+        return;
+    }
+
+    let kernel = select_kernel();
+    let threads = if m * n * k >= PARALLEL_GEMM { num_threads() } else { 1 };
+    let c = SyncPtr(c);
+    let mut packed_b = vec![0.0f32; KC * NC.min(n.next_multiple_of(NR))];
+    let mut packed_a = vec![0.0f32; MC.min(m.next_multiple_of(MR)) * KC];
+
+    for jc in (0..n).step_by(NC) {
+        let nc = NC.min(n - jc);
+        let panels = nc.div_ceil(NR);
+        // With few row blocks (down to a single row when decoding), split the columns too
+        let row_blocks = m.div_ceil(MC);
+        let groups = threads.div_ceil(row_blocks).min(panels);
+        let group_width = panels.div_ceil(groups) * NR;
+
+        for pc in (0..k).step_by(KC) {
+            let kc = KC.min(k - pc);
+            // The first depth block overwrites C, later ones accumulate into it
+            let accumulate = pc > 0;
+
+            let b_block = b.block(pc, jc);
+            let pack = |(panel, dst): (usize, &mut [f32])| {
+                let jr = panel * NR;
+                pack_b(kc, NR.min(nc - jr), b_block.block(0, jr), dst)
+            };
+            let packed_b = &mut packed_b[..panels * NR * kc];
+            if threads > 1 {
+                install(|| packed_b.par_chunks_mut(NR * kc).enumerate().for_each(pack));
+            } else {
+                packed_b.chunks_mut(NR * kc).enumerate().for_each(pack);
+            }
+            let packed_b = &*packed_b;
+
+            let task = |t: usize, packed_a: &mut [f32]| {
+                let (ic, j0) = ((t / groups) * MC, (t % groups) * group_width);
+                if j0 >= nc {
+                    return;
+                }
+                let mc = MC.min(m - ic);
+                pack_a(mc, kc, a.block(ic, pc), packed_a);
+                let c_block = c.get().add(ic * ldc + jc + j0);
+                macro_kernel(kernel, mc, group_width.min(nc - j0), kc, packed_a, &packed_b[j0 * kc..], c_block, ldc, accumulate);
+            };
+            if threads > 1 {
+                let len = packed_a.len();
+                install(|| (0..row_blocks * groups).into_par_iter().for_each_init(|| vec![0.0f32; len], |buf, t| task(t, buf)));
+            } else {
+                (0..row_blocks * groups).for_each(|t| task(t, &mut packed_a));
+            }
+        }
+    }
+}
+
+/// Micro-kernel: `MR x NR` tile of `A_panel B_panel` over depth `kc`, stored (or added when
+/// `accumulate`) to `c` with row stride `ldc`.
+type MicroKernel = unsafe fn(kc: usize, a: *const f32, b: *const f32, c: *mut f32, ldc: usize, accumulate: bool);
+
+fn select_kernel() -> MicroKernel {
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    if crate::ops::simd::avx2_available() {
+        return crate::ops::simd::avx2::gemm_6x16;
+    }
+    kernel_scalar
+}
+
+/// Portable micro-kernel; the fixed-size accumulator lets the compiler vectorize it.
+unsafe fn kernel_scalar(kc: usize, a: *const f32, b: *const f32, c: *mut f32, ldc: usize, accumulate: bool) {
+    let mut acc = [[0.0f32; NR]; MR];
+    for p in 0..kc {
+        let bp = std::slice::from_raw_parts(b.add(p * NR), NR);
+        for (i, row) in acc.iter_mut().enumerate() {
+            let av = *a.add(p * MR + i);
+            for (dst, &bv) in row.iter_mut().zip(bp) {
+                *dst += av * bv;
+            }
+        }
+    }
+    for (i, row) in acc.iter().enumerate() {
+        for (j, &v) in row.iter().enumerate() {
+            let out = c.add(i * ldc + j);
+            *out = if accumulate { *out + v } else { v };
+        }
+    }
+}
+
+/// Runs the micro-kernel over every tile of an `mc x nc` block. Edge tiles go through a
+/// scratch tile so the kernel always writes a full `MR x NR`.
+#[allow(clippy::too_many_arguments)]
+unsafe fn macro_kernel(
+    kernel: MicroKernel,
+    mc: usize,
+    nc: usize,
+    kc: usize,
+    packed_a: &[f32],
+    packed_b: &[f32],
+    c: *mut f32,
+    ldc: usize,
+    accumulate: bool,
+) {
+    let mut scratch = [0.0f32; MR * NR];
+    for jr in (0..nc).step_by(NR) {
+        let nr = NR.min(nc - jr);
+        let b = packed_b.as_ptr().add(jr * kc);
+        for ir in (0..mc).step_by(MR) {
+            let mr = MR.min(mc - ir);
+            let a = packed_a.as_ptr().add(ir * kc);
+            let tile = c.add(ir * ldc + jr);
+            if mr == MR && nr == NR {
+                kernel(kc, a, b, tile, ldc, accumulate);
+                continue;
+            }
+            kernel(kc, a, b, scratch.as_mut_ptr(), NR, false);
+            for i in 0..mr {
+                for j in 0..nr {
+                    let out = tile.add(i * ldc + j);
+                    let v = scratch[i * NR + j];
+                    *out = if accumulate { *out + v } else { v };
+                }
+            }
+        }
+    }
+}
+
+/// Packs one `kc x nr` column panel of B into `dst`, row by row (`NR` values per depth
+/// step), zero-padding columns past `nr`.
+unsafe fn pack_b(kc: usize, nr: usize, b: MatRef, dst: &mut [f32]) {
+    for p in 0..kc {
+        let row = &mut dst[p * NR..(p + 1) * NR];
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = if j < nr { b.at(p, j) } else { 0.0 };
+        }
+    }
+}
+
+/// Packs an `mc x kc` block of A into row panels of height MR, each stored column by column
+/// (`MR` values per depth step). The last panel is zero-padded.
+unsafe fn pack_a(mc: usize, kc: usize, a: MatRef, packed: &mut [f32]) {
+    for (panel, ir) in (0..mc).step_by(MR).enumerate() {
+        let mr = MR.min(mc - ir);
+        let dst = &mut packed[panel * MR * kc..(panel + 1) * MR * kc];
+        for p in 0..kc {
+            let col = &mut dst[p * MR..(p + 1) * MR];
+            for (i, v) in col.iter_mut().enumerate() {
+                *v = if i < mr { a.at(ir + i, p) } else { 0.0 };
+            }
+        }
+    }
+}
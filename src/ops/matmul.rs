@@ -1,5 +1,13 @@
+use std::sync::Arc;
+use rayon::prelude::*;
 use crate::tensor::{Tensor, DType};
 use crate::autograd::node::Node;
+use crate::autograd::grad_mode::needs_grad;
+use crate::error::{Result, TensorError};
+use crate::ops::binary::reduce_to_shape;
+use crate::ops::gemm::{gemm, MatRef};
+use crate::ops::strided::for_each_offset;
+use crate::parallel::{install, should_parallelize, SyncPtr};
 
 
 #[derive(Debug)]
@@ -17,145 +25,171 @@ impl Node for MatmulNode {
         // C = A @ B
         // dA = grad @ B.T
         // dB = A.T @ grad
+        // Batch dims that were broadcast in the forward pass are summed back out.
         
-        let dlhs = matmul(grad, &self.rhs.t());
-        let drhs = matmul(&self.lhs.t(), grad);
+        let dlhs = reduce_to_shape(&matmul(grad, &self.rhs.t()), &self.lhs.shape);
+        let drhs = reduce_to_shape(&matmul(&self.lhs.t(), grad), &self.rhs.shape);
         
         vec![dlhs, drhs]
     }
 }
 
-pub fn matmul(lhs: &Tensor, rhs: &Tensor) -> Tensor {
-    assert_eq!(lhs.dtype, DType::F32, "Only F32 supported for now");
-    assert_eq!(rhs.dtype, DType::F32);
-    
-    // Shapes: [..., M, K] x [..., K, N] -> [..., M, N]
-    // Simplify: 2D only for now
-    assert_eq!(lhs.shape.len(), 2);
-    assert_eq!(rhs.shape.len(), 2);
-    
-    let m = lhs.shape[0];
-    let k = lhs.shape[1];
-    let k2 = rhs.shape[0];
-    let n = rhs.shape[1];
-    
-    assert_eq!(k, k2, "Dimension mismatch");
-    
-    let _blocks_m = (m + 31) / 32;
-    let _blocks_n = (n + 31) / 32;
-    let _blocks_k = (k + 31) / 32;
-    // 32 is a small block size, usually 64-256 for L2 cache.
-    // L1 cache blocking is typically smaller (register tile).
-    
-    let mut output = Tensor::zeros(vec![m, n], DType::F32);
-    
-    // Pointers
-    let a_ptr = lhs.storage.as_slice().as_ptr(); // TODO: Add offset support
-    let b_ptr = rhs.storage.as_slice().as_ptr();
-    let c_ptr = output.storage.as_ptr() as *mut f32; // Use raw pointer from shared storage
+/// Matrix product of F32 tensors `[..., M, K] x [..., K, N] -> [..., M, N]`.
+/// The leading (batch) dims broadcast NumPy-style, e.g. `[B, H, S, D] x [D, S] -> [B, H, S, S]`.
+/// Operands are read through their strides, so transposed views need no copy.
+pub fn try_matmul(lhs: &Tensor, rhs: &Tensor) -> Result<Tensor> {
+    if lhs.dtype != DType::F32 {
+        return Err(TensorError::Unsupported(format!("matmul on {:?}", lhs.dtype)));
+    }
+    if rhs.dtype != lhs.dtype {
+        return Err(TensorError::DTypeMismatch { expected: lhs.dtype, found: rhs.dtype });
+    }
+    if lhs.shape.len() < 2 || rhs.shape.len() < 2 {
+        return Err(TensorError::Unsupported(format!(
+            "matmul of {:?} x {:?}, operands need at least 2 dims", lhs.shape, rhs.shape
+        )));
+    }
     
-    // Naive Tiled Implementation
-    // Optimizations to add: SIMD, Register Blocking, Cache Blocking
-    // This is essentially just a triple loop but structured for adding tiling later.
+    let (lhs_batch, lhs_mat) = lhs.shape.split_at(lhs.shape.len() - 2);
+    let (rhs_batch, rhs_mat) = rhs.shape.split_at(rhs.shape.len() - 2);
+    let (m, k) = (lhs_mat[0], lhs_mat[1]);
+    let (k2, n) = (rhs_mat[0], rhs_mat[1]);
     
-    // Current: Simple Triple Loop
-    // Use raw pointers for speed (unsafe)
-    unsafe {
-        let a_data = std::slice::from_raw_parts(a_ptr as *const f32, lhs.numel());
-        let b_data = std::slice::from_raw_parts(b_ptr as *const f32, rhs.numel());
-        let c_data = std::slice::from_raw_parts_mut(c_ptr as *mut f32, output.numel());
-        
-        let a_stride_m = lhs.strides[0];
-        let a_stride_k = lhs.strides[1];
-        let b_stride_k = rhs.strides[0];
-        let b_stride_n = rhs.strides[1];
-        let c_stride_m = output.strides[0];
-        let c_stride_n = output.strides[1];
-
-        // Parallelize m dimension with Rayon if available? 
-        // For now single threaded.
-        
-        for i in 0..m {
-            for j in 0..n {
-                let mut sum = 0.0;
-                for p in 0..k { // dot product
-                     let a_val = *a_data.get_unchecked(i * a_stride_m + p * a_stride_k);
-                     let b_val = *b_data.get_unchecked(p * b_stride_k + j * b_stride_n);
-                     sum += a_val * b_val;
-                }
-                *c_data.get_unchecked_mut(i * c_stride_m + j * c_stride_n) = sum;
-            }
-        }
+    if k != k2 {
+        let expected = [rhs_batch, &[k, n]].concat();
+        return Err(TensorError::ShapeMismatch { expected, found: rhs.shape.clone() });
+    }
+    let batch = Tensor::broadcast_shape(lhs_batch, rhs_batch).ok_or_else(|| TensorError::IncompatibleShapes {
+        lhs: lhs.shape.clone(),
+        rhs: rhs.shape.clone(),
+    })?;
+    
+    let mut output = Tensor::zeros([batch.as_slice(), &[m, n]].concat(), DType::F32);
+    
+    // Pointers to each operand's first element (offsets are in bytes)
+    let a_ptr = unsafe { lhs.storage.as_ptr().add(lhs.offset) };
+    let b_ptr = unsafe { rhs.storage.as_ptr().add(rhs.offset) };
+    let c_ptr = SyncPtr(output.storage.as_ptr() as *mut f32); // Use raw pointer from shared storage
+    
+    // Element offsets of each matrix in the batch; broadcast batch dims have stride 0
+    let a_strides = lhs.broadcast_strides(&[batch.as_slice(), &[m, k]].concat());
+    let b_strides = rhs.broadcast_strides(&[batch.as_slice(), &[k, n]].concat());
+    let nb = batch.len();
+    let mut bases = Vec::with_capacity(batch.iter().product());
+    for_each_offset(&batch, [&a_strides[..nb], &b_strides[..nb]], |i, [a, b]| bases.push((a, b, i * m * n)));
+    
+    // Packed, cache-blocked GEMM per matrix (AVX2 + FMA micro-kernel when the CPU has it)
+    let a = MatRef { ptr: a_ptr as *const f32, rs: a_strides[nb], cs: a_strides[nb + 1] };
+    let b = MatRef { ptr: b_ptr as *const f32, rs: b_strides[nb], cs: b_strides[nb + 1] };
+    let run = |&(a_base, b_base, c_base): &(usize, usize, usize)| unsafe {
+        gemm(m, n, k, a.offset(a_base), b.offset(b_base), c_ptr.get().add(c_base), n);
+    };
+    if bases.len() > 1 && should_parallelize(bases.len() * m * n * k) {
+        install(|| bases.par_iter().for_each(run));
+    } else {
+        bases.iter().for_each(run);
     }
     
     // Attach graph
-    if lhs.requires_grad || rhs.requires_grad {
+    if needs_grad(&[lhs, rhs]) {
         output.requires_grad = true;
-        output.ctx = Some(Box::new(MatmulNode {
+        output.ctx = Some(Arc::new(MatmulNode {
             lhs: lhs.clone(),
             rhs: rhs.clone(),
         }));
     }
     
-    output
+    Ok(output)
 }
 
-pub fn matmul_int4(input: &Tensor, weight_packed: &Tensor, scales: &Tensor, bias: &Option<Tensor>) -> Tensor {
+/// Panicking form of [`try_matmul`].
+pub fn matmul(lhs: &Tensor, rhs: &Tensor) -> Tensor {
+    try_matmul(lhs, rhs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// F32 activations times packed 4-bit weights: `[M, K] x [N, K/2] -> [M, N]`, plus optional `[N]` bias.
+pub fn try_matmul_int4(input: &Tensor, weight_packed: &Tensor, scales: &Tensor, bias: &Option<Tensor>) -> Result<Tensor> {
     // specialized forward pass
     // input: [M, K]
     // weight: [N, K/2]
     // output: [M, N]
     
+    if input.dtype != DType::F32 {
+        return Err(TensorError::DTypeMismatch { expected: DType::F32, found: input.dtype });
+    }
+    if weight_packed.dtype != DType::I8 {
+        return Err(TensorError::DTypeMismatch { expected: DType::I8, found: weight_packed.dtype });
+    }
+    if scales.dtype != DType::F32 {
+        return Err(TensorError::DTypeMismatch { expected: DType::F32, found: scales.dtype });
+    }
+    if input.shape.len() != 2 || !input.shape[1].is_multiple_of(2) {
+        return Err(TensorError::Unsupported(format!(
+            "matmul_int4 input {:?}, expected [M, K] with even K", input.shape
+        )));
+    }
+
     let m = input.shape[0];
     let k = input.shape[1];
-    let n = weight_packed.shape[0];
-    
-    // assert k % 2 == 0
-    
+    let n = weight_packed.shape.first().copied().unwrap_or(0);
+
+    if weight_packed.shape != [n, k / 2] {
+        return Err(TensorError::ShapeMismatch { expected: vec![n, k / 2], found: weight_packed.shape.clone() });
+    }
+    if scales.shape != [n] {
+        return Err(TensorError::ShapeMismatch { expected: vec![n], found: scales.shape.clone() });
+    }
+
     let output = Tensor::zeros(vec![m, n], DType::F32);
     
-     unsafe {
-          let a_ptr = input.storage.as_ptr() as *const f32;
-          let w_ptr = weight_packed.storage.as_ptr() as *const u8;
-          let s_ptr = scales.storage.as_ptr() as *const f32;
-          let c_ptr = output.storage.as_ptr() as *mut f32;
-
-         
-         let a_stride = input.strides[0]; // M stride
-         let w_stride = weight_packed.strides[0]; // N stride in packed
-         
-         for i in 0..m {
-             for j in 0..n {
-                 let scale = *s_ptr.add(j);
-                 let mut sum = 0.0;
-                 
-                 for p in 0..(k/2) {
-                     let packed = *w_ptr.add(j * w_stride + p);
-                     let low = (packed & 0x0F) as i8;
-                     let high = ((packed >> 4) & 0x0F) as i8;
-                     // Convert raw 4-bit uint to signed int if needed (usually offset binary or 2s comp)
-                     // Let's assume offset binary for simplicity or just raw value * scale.
-                     // A standard scheme: val = (nibble - 8) * scale
-                     
-                     let val_low = (low as f32 - 8.0) * scale;
-                     let val_high = (high as f32 - 8.0) * scale;
-                     
-                     let a_val1 = *a_ptr.add(i * a_stride + (p * 2));
-                     let a_val2 = *a_ptr.add(i * a_stride + (p * 2 + 1));
-                     
-                     sum += a_val1 * val_low + a_val2 * val_high;
-                 }
-                 
-                 *c_ptr.add(i * output.strides[0] + j) = sum;
-             }
-         }
+    // Every operand is read at its own offset and strides, so slices of a larger buffer
+    // (e.g. a KV cache or a shard of a packed weight) need no copy.
+    unsafe {
+        let a_ptr = input.storage.as_ptr().add(input.offset) as *const f32;
+        let w_ptr = weight_packed.storage.as_ptr().add(weight_packed.offset);
+        let s_ptr = scales.storage.as_ptr().add(scales.offset) as *const f32;
+        let c_ptr = output.storage.as_ptr() as *mut f32;
+        
+        let (a_stride_m, a_stride_k) = (input.strides[0], input.strides[1]);
+        let (w_stride_n, w_stride_k) = (weight_packed.strides[0], weight_packed.strides[1]);
+        let s_stride = scales.strides[0];
+        
+        for i in 0..m {
+            let a_row = a_ptr.add(i * a_stride_m);
+            for j in 0..n {
+                let scale = *s_ptr.add(j * s_stride);
+                let w_row = w_ptr.add(j * w_stride_n);
+                let mut sum = 0.0;
+                
+                for p in 0..(k/2) {
+                    let packed = *w_row.add(p * w_stride_k);
+                    let low = (packed & 0x0F) as i8;
+                    let high = ((packed >> 4) & 0x0F) as i8;
+                    // Nibbles are offset binary: val = (nibble - 8) * scale
+                    let val_low = (low as f32 - 8.0) * scale;
+                    let val_high = (high as f32 - 8.0) * scale;
+                    
+                    let a_val1 = *a_row.add(2 * p * a_stride_k);
+                    let a_val2 = *a_row.add((2 * p + 1) * a_stride_k);
+                    
+                    sum += a_val1 * val_low + a_val2 * val_high;
+                }
+                
+                *c_ptr.add(i * output.strides[0] + j) = sum;
+            }
+        }
     }
     
-    if let Some(_b) = bias {
-        // Add bias
-        // crate::ops::binary::add(&output, b)
+    if let Some(b) = bias {
+        // [N] bias broadcasts over the M rows
+        return crate::ops::binary::try_add(&output, b);
     }
     
-    output
+    Ok(output)
+}
+
+/// Panicking form of [`try_matmul_int4`].
+pub fn matmul_int4(input: &Tensor, weight_packed: &Tensor, scales: &Tensor, bias: &Option<Tensor>) -> Tensor {
+    try_matmul_int4(input, weight_packed, scales, bias).unwrap_or_else(|e| panic!("{}", e))
 }
 
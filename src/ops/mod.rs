@@ -1,7 +1,14 @@
 pub mod binary;
+pub(crate) mod gemm;
 pub mod matmul;
+pub mod precision;
+pub mod reduce;
+pub(crate) mod simd;
+pub mod softmax;
+pub(crate) mod strided;
 pub mod unary;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 pub mod tests;
 
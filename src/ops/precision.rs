@@ -0,0 +1,43 @@
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+// Thread-local switch between fast SIMD approximations and exact scalar math
+// for transcendental pointwise kernels (exp, log, sin, cos, rsqrt, sigmoid, tanh, GELU, softplus,
+// softmax). Kernels that are exact in SIMD (sqrt, abs, neg, reciprocal, clamp, leaky ReLU) ignore it.
+
+/// Accuracy requested from pointwise kernels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Precision {
+    /// SIMD polynomial approximations, within a few ulp on the usual input ranges.
+    #[default]
+    Fast,
+    /// Scalar `std` math, element by element.
+    Exact,
+}
+
+thread_local! {
+    static PRECISION: Cell<Precision> = const { Cell::new(Precision::Fast) };
+}
+
+/// Precision used by pointwise kernels on this thread.
+pub fn precision() -> Precision {
+    PRECISION.with(|p| p.get())
+}
+
+/// RAII guard returned by [`with_precision`]. Not `Send`: the mode is per-thread.
+pub struct PrecisionGuard {
+    prev: Precision,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for PrecisionGuard {
+    fn drop(&mut self) {
+        PRECISION.with(|p| p.set(self.prev));
+    }
+}
+
+/// Uses `precision` for pointwise kernels on this thread until the guard is dropped.
+pub fn with_precision(precision: Precision) -> PrecisionGuard {
+    let prev = PRECISION.with(|p| p.replace(precision));
+    PrecisionGuard { prev, _not_send: PhantomData }
+}
@@ -0,0 +1,405 @@
+use std::ops::Range;
+use std::sync::Arc;
+use rayon::prelude::*;
+use crate::autograd::grad_mode::needs_grad;
+use crate::autograd::node::Node;
+use crate::error::{Result, TensorError};
+use crate::ops::binary::{add, mul, mul_scalar};
+use crate::ops::strided::{for_each_offset, map_binary};
+use crate::parallel::{install, should_parallelize};
+use crate::tensor::{DType, Shape, Tensor};
+
+// Reductions over one dim (`Some(dim)`) or over every element (`None`).
+// Each output element folds one strided "lane" of the input. Large inputs fold their lanes on
+// the engine's pool. Long lanes are always split into fixed-size chunks whose partial results are
+// combined left to right, serially or not, so results do not depend on the number of threads.
+// max/min and argmax/argmin propagate NaN: a lane holding NaN reduces to NaN (its first index).
+
+/// Elements folded per chunk (and per rayon task) within one lane.
+const CHUNK: usize = 1 << 13;
+
+/// One lane of an F32 input: element `i` lives at `ptr + i * stride`.
+#[derive(Clone, Copy)]
+struct Lane {
+    ptr: *const f32,
+    stride: usize,
+}
+
+// Lanes only read from storage kept alive by the tensor being reduced
+unsafe impl Send for Lane {}
+unsafe impl Sync for Lane {}
+
+impl Lane {
+    /// The lane starting `base` elements further on, with the same stride.
+    fn at(self, base: usize) -> Lane {
+        Lane { ptr: unsafe { self.ptr.add(base) }, stride: self.stride }
+    }
+
+    fn values(self, range: Range<usize>) -> impl Iterator<Item = (usize, f32)> {
+        range.map(move |i| (i, unsafe { *self.ptr.add(i * self.stride) }))
+    }
+}
+
+/// Folds every lane of `input` along `dim` (all elements when `None`) into one value,
+/// in row-major order of the reduced shape.
+fn fold_lanes<T, F, C>(input: &Tensor, dim: Option<usize>, fold: F, combine: C) -> Vec<T>
+where
+    T: Send,
+    F: Fn(Lane, Range<usize>) -> T + Sync,
+    C: Fn(T, T) -> T + Sync,
+{
+    // A full reduction is a single lane over a dense copy
+    let dense;
+    let (source, bases, stride, len) = match dim {
+        Some(d) => {
+            let (outer_shape, outer_strides) = drop_dim(&input.shape, &input.strides, d);
+            let mut bases = Vec::with_capacity(outer_shape.iter().product());
+            for_each_offset(&outer_shape, [&outer_strides], |_, [b]| bases.push(b));
+            (input, bases, input.strides[d], input.shape[d])
+        }
+        None => {
+            dense = if input.is_contiguous() { input.clone() } else { input.detach().contiguous() };
+            (&dense, vec![0], 1, input.numel())
+        }
+    };
+
+    let parallel = should_parallelize(input.numel());
+    let origin = Lane { ptr: unsafe { source.storage.as_ptr().add(source.offset) as *const f32 }, stride };
+    let fold_lane = |&base: &usize| -> T {
+        let lane = origin.at(base);
+        if len <= CHUNK {
+            return fold(lane, 0..len);
+        }
+        let chunk = |c: usize| fold(lane, c * CHUNK..((c + 1) * CHUNK).min(len));
+        let partials: Vec<T> = if parallel {
+            (0..len.div_ceil(CHUNK)).into_par_iter().map(chunk).collect()
+        } else {
+            (0..len.div_ceil(CHUNK)).map(chunk).collect()
+        };
+        partials.into_iter().reduce(&combine).unwrap()
+    };
+
+    if parallel {
+        install(|| bases.par_iter().map(fold_lane).collect())
+    } else {
+        bases.iter().map(fold_lane).collect()
+    }
+}
+
+fn drop_dim(shape: &[usize], strides: &[usize], dim: usize) -> (Shape, Vec<usize>) {
+    let mut shape = shape.to_vec();
+    let mut strides = strides.to_vec();
+    shape.remove(dim);
+    strides.remove(dim);
+    (shape, strides)
+}
+
+/// Output shape of a reduction. With `keepdim` reduced dims stay as size 1.
+fn reduced_shape(shape: &[usize], dim: Option<usize>, keepdim: bool) -> Shape {
+    match (dim, keepdim) {
+        (None, true) => vec![1; shape.len()],
+        (None, false) => vec![],
+        (Some(d), true) => {
+            let mut out = shape.to_vec();
+            out[d] = 1;
+            out
+        }
+        (Some(d), false) => drop_dim(shape, shape, d).0,
+    }
+}
+
+/// Number of elements folded into each output element.
+fn lane_len(input: &Tensor, dim: Option<usize>) -> usize {
+    dim.map_or(input.numel(), |d| input.shape[d])
+}
+
+/// Validates the input of a reduction. `nonempty` rejects empty lanes (max, min, arg*).
+fn check_reduce(input: &Tensor, dim: Option<usize>, nonempty: bool) -> Result<()> {
+    if input.dtype != DType::F32 {
+        return Err(TensorError::Unsupported(format!("reductions on {:?}", input.dtype)));
+    }
+    if let Some(d) = dim {
+        if d >= input.shape.len() {
+            return Err(TensorError::OutOfBounds { index: vec![d], shape: input.shape.clone() });
+        }
+    }
+    if nonempty && lane_len(input, dim) == 0 {
+        return Err(TensorError::Unsupported("max/min/argmax/argmin of an empty dimension".to_string()));
+    }
+    Ok(())
+}
+
+/// Gradient of the reduced output broadcast back over the input's shape.
+fn expand_grad(grad: &Tensor, input: &Tensor, dim: Option<usize>) -> Tensor {
+    grad.reshape(&reduced_shape(&input.shape, dim, true)).expand(&input.shape)
+}
+
+#[derive(Debug)]
+pub struct SumNode {
+    input: Tensor,
+    dim: Option<usize>,
+}
+
+impl Node for SumNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // Every summed element contributes with weight 1
+        vec![expand_grad(grad, &self.input, self.dim)]
+    }
+}
+
+/// Sum over `dim`, or over all elements when `dim` is `None`.
+pub fn try_sum(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Result<Tensor> {
+    check_reduce(input, dim, false)?;
+    let values = fold_lanes(input, dim, |lane, range| lane.values(range).map(|(_, x)| x).sum::<f32>(), |a, b| a + b);
+    let output = Tensor::from_vec_f32(values, reduced_shape(&input.shape, dim, keepdim));
+
+    if needs_grad(&[input]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(SumNode { input: input.clone(), dim }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_sum`].
+pub fn sum(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Tensor {
+    try_sum(input, dim, keepdim).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Mean over `dim`, or over all elements when `dim` is `None`.
+/// An empty reduction yields NaN.
+pub fn try_mean(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Result<Tensor> {
+    let total = try_sum(input, dim, keepdim)?;
+    Ok(mul_scalar(&total, (lane_len(input, dim) as f32).recip()))
+}
+
+/// Panicking form of [`try_mean`].
+pub fn mean(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Tensor {
+    try_mean(input, dim, keepdim).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct ReduceSelectNode {
+    input: Tensor,
+    output: Tensor, // Detached, keepdim-shaped
+    dim: Option<usize>,
+}
+
+impl Node for ReduceSelectNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // Gradient goes to the elements equal to the result; ties split it evenly,
+        // like `maximum` / `minimum`. A NaN result is shared by the NaN elements.
+        let hits = map_binary(&self.input, &self.output, |x, m| {
+            if x == m || (x.is_nan() && m.is_nan()) { 1.0 } else { 0.0 }
+        });
+        let counts = sum(&hits, self.dim, true);
+        let weights = map_binary(&hits, &counts, |h, c| h / c);
+        vec![mul(&expand_grad(grad, &self.input, self.dim), &weights)]
+    }
+}
+
+fn select(input: &Tensor, dim: Option<usize>, keepdim: bool, is_max: bool) -> Result<Tensor> {
+    check_reduce(input, dim, true)?;
+    // `f32::max`/`min` would drop NaN; keep it instead
+    let pick = move |a: f32, b: f32| {
+        if a.is_nan() || b.is_nan() {
+            f32::NAN
+        } else if is_max {
+            a.max(b)
+        } else {
+            a.min(b)
+        }
+    };
+    let values = fold_lanes(
+        input,
+        dim,
+        |lane, range| lane.values(range).map(|(_, x)| x).reduce(pick).unwrap(),
+        pick,
+    );
+    let output = Tensor::from_vec_f32(values, reduced_shape(&input.shape, dim, keepdim));
+
+    if needs_grad(&[input]) {
+        let kept = output.reshape(&reduced_shape(&input.shape, dim, true));
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(ReduceSelectNode { input: input.clone(), output: kept, dim }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Maximum over `dim`, or over all elements when `dim` is `None`. NaN propagates.
+pub fn try_max(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Result<Tensor> {
+    select(input, dim, keepdim, true)
+}
+
+/// Panicking form of [`try_max`].
+pub fn max(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Tensor {
+    try_max(input, dim, keepdim).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Minimum over `dim`, or over all elements when `dim` is `None`. NaN propagates.
+pub fn try_min(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Result<Tensor> {
+    select(input, dim, keepdim, false)
+}
+
+/// Panicking form of [`try_min`].
+pub fn min(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Tensor {
+    try_min(input, dim, keepdim).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct ProdNode {
+    input: Tensor,
+    dim: Option<usize>,
+}
+
+impl Node for ProdNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // d/dx_i prod(x) = product of the other elements, which stays exact when x_i == 0.
+        // When a graph is being recorded the factor is built from recorded ops instead.
+        let others = if needs_grad(&[grad, &self.input]) {
+            recorded_prod_of_others(&self.input, self.dim)
+        } else {
+            prod_of_others(&self.input, self.dim)
+        };
+        vec![mul(&expand_grad(grad, &self.input, self.dim), &others)]
+    }
+}
+
+/// Differentiable form of [`prod_of_others`]. Each lane is repeated once per element with that
+/// element replaced by 1, then reduced with `prod`, so it stays exact at zeros. Quadratic in the
+/// lane length, which is why the plain backward uses the prefix/suffix products.
+fn recorded_prod_of_others(input: &Tensor, dim: Option<usize>) -> Tensor {
+    let (lanes, dim) = match dim {
+        Some(d) => (input.clone(), d),
+        None => (input.reshape(&[input.numel()]), 0),
+    };
+    let n = lanes.shape[dim];
+    let last = lanes.shape.len() - 1;
+
+    let eye: Vec<f32> = (0..n * n).map(|k| if k % (n + 1) == 0 { 1.0 } else { 0.0 }).collect();
+    let off_diagonal = Tensor::from_vec_f32(eye.iter().map(|e| 1.0 - e).collect(), vec![n, n]);
+    let eye = Tensor::from_vec_f32(eye, vec![n, n]);
+
+    // rows[..., i, j] = x_j for j != i and 1 for j == i; the lane is moved last first
+    let rows = add(&mul(&lanes.transpose(dim, last).unsqueeze(last), &off_diagonal), &eye);
+    prod(&rows, Some(last + 1), false).transpose(dim, last).reshape(&input.shape)
+}
+
+/// For each element, the product of every other element of its lane, laid out densely like `input`.
+fn prod_of_others(input: &Tensor, dim: Option<usize>) -> Tensor {
+    let output = Tensor::zeros(input.shape.clone(), DType::F32);
+    let dense = Tensor::default_strides(&input.shape);
+    let (outer_shape, in_outer, out_outer, in_stride, out_stride, len) = match dim {
+        Some(d) => {
+            let (shape, in_strides) = drop_dim(&input.shape, &input.strides, d);
+            let (_, out_strides) = drop_dim(&input.shape, &dense, d);
+            (shape, in_strides, out_strides, input.strides[d], dense[d], input.shape[d])
+        }
+        None => (vec![], vec![], vec![], 1, 1, input.numel()),
+    };
+
+    // A full reduction reads through a dense copy, like `fold_lanes`
+    let source = match dim {
+        Some(_) => input.clone(),
+        None => input.detach().contiguous(),
+    };
+
+    unsafe {
+        let in_ptr = source.storage.as_ptr().add(source.offset) as *const f32;
+        let out_ptr = output.storage.as_ptr() as *mut f32;
+        for_each_offset(&outer_shape, [&in_outer, &out_outer], |_, [a, o]| {
+            // Exclusive prefix products first, then multiply in the suffix products
+            let mut acc = 1.0;
+            for i in 0..len {
+                *out_ptr.add(o + i * out_stride) = acc;
+                acc *= *in_ptr.add(a + i * in_stride);
+            }
+            acc = 1.0;
+            for i in (0..len).rev() {
+                *out_ptr.add(o + i * out_stride) *= acc;
+                acc *= *in_ptr.add(a + i * in_stride);
+            }
+        });
+    }
+
+    output
+}
+
+/// Product over `dim`, or over all elements when `dim` is `None`.
+pub fn try_prod(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Result<Tensor> {
+    check_reduce(input, dim, false)?;
+    let values = fold_lanes(input, dim, |lane, range| lane.values(range).map(|(_, x)| x).product::<f32>(), |a, b| a * b);
+    let output = Tensor::from_vec_f32(values, reduced_shape(&input.shape, dim, keepdim));
+
+    if needs_grad(&[input]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(ProdNode { input: input.clone(), dim }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_prod`].
+pub fn prod(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Tensor {
+    try_prod(input, dim, keepdim).unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn arg_select(input: &Tensor, dim: Option<usize>, keepdim: bool, is_max: bool) -> Result<Tensor> {
+    check_reduce(input, dim, true)?;
+    // Strictly better only, so ties resolve to the first index (chunks combine in order).
+    // NaN beats any number, so the first NaN wins, matching `max`/`min`.
+    let better = move |b: f32, a: f32| {
+        !a.is_nan() && (b.is_nan() || if is_max { b > a } else { b < a })
+    };
+    let pick = move |a: (usize, f32), b: (usize, f32)| if better(b.1, a.1) { b } else { a };
+    let winners = fold_lanes(input, dim, |lane, range| lane.values(range).reduce(pick).unwrap(), pick);
+
+    // Indices along `dim`, or into the flattened input for `None`
+    let indices: Vec<i64> = winners.into_iter().map(|(i, _)| i as i64).collect();
+    let output = Tensor::zeros(reduced_shape(&input.shape, dim, keepdim), DType::I64);
+    unsafe {
+        std::ptr::copy_nonoverlapping(indices.as_ptr(), output.storage.as_ptr() as *mut i64, indices.len());
+    }
+    Ok(output)
+}
+
+/// Index of the maximum over `dim` (into the flattened input when `None`), as an I64 tensor.
+/// Ties resolve to the first occurrence, and a NaN counts as the maximum. Not differentiable.
+pub fn try_argmax(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Result<Tensor> {
+    arg_select(input, dim, keepdim, true)
+}
+
+/// Panicking form of [`try_argmax`].
+pub fn argmax(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Tensor {
+    try_argmax(input, dim, keepdim).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Index of the minimum over `dim` (into the flattened input when `None`), as an I64 tensor.
+/// Ties resolve to the first occurrence, and a NaN counts as the minimum. Not differentiable.
+pub fn try_argmin(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Result<Tensor> {
+    arg_select(input, dim, keepdim, false)
+}
+
+/// Panicking form of [`try_argmin`].
+pub fn argmin(input: &Tensor, dim: Option<usize>, keepdim: bool) -> Tensor {
+    try_argmin(input, dim, keepdim).unwrap_or_else(|e| panic!("{}", e))
+}
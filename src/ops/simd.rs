@@ -0,0 +1,422 @@
+use crate::ops::precision::{precision, Precision};
+
+// Explicit SIMD kernels, compiled in with the `avx2` feature and picked at runtime.
+// Every kernel here has a scalar reference next to its caller; callers must check
+// `avx2_available()` before calling into `avx2`.
+
+/// Pointwise F32 functions with a dense SIMD kernel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Kernel {
+    Sigmoid,
+    Tanh,
+    Silu,
+    Gelu,
+    GeluTanh,
+    /// `beta`
+    Softplus(f32),
+    /// `negative_slope`
+    LeakyRelu(f32),
+    Exp,
+    Log,
+    Sin,
+    Cos,
+    Rsqrt,
+    Sqrt,
+    Abs,
+    Neg,
+    Reciprocal,
+    Clamp(f32, f32),
+}
+
+impl Kernel {
+    /// Whether the SIMD kernel is exactly rounded, so it may run under `Precision::Exact`.
+    fn is_exact(self) -> bool {
+        matches!(
+            self,
+            Kernel::Sqrt | Kernel::Abs | Kernel::Neg | Kernel::Reciprocal | Kernel::Clamp(..) | Kernel::LeakyRelu(_)
+        )
+    }
+}
+
+/// Applies `kernel` to a dense slice with SIMD. Returns `false`, writing nothing, when no
+/// SIMD kernel is available on this build or CPU, or when it is approximate and the thread
+/// asked for `Precision::Exact`.
+pub(crate) fn map_dense(kernel: Kernel, src: &[f32], dst: &mut [f32]) -> bool {
+    assert_eq!(src.len(), dst.len());
+    if !kernel.is_exact() && precision() == Precision::Exact {
+        return false;
+    }
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    if avx2_available() {
+        use rayon::prelude::*;
+        use crate::parallel::{install, should_parallelize};
+        const CHUNK: usize = 1 << 13;
+        if should_parallelize(src.len()) {
+            install(|| {
+                src.par_chunks(CHUNK).zip(dst.par_chunks_mut(CHUNK)).for_each(|(s, d)| unsafe { avx2::map_dense(kernel, s, d) })
+            });
+        } else {
+            unsafe { avx2::map_dense(kernel, src, dst) };
+        }
+        return true;
+    }
+    let _ = (kernel, src, dst);
+    false
+}
+
+/// True when the running CPU supports the AVX2 + FMA kernels.
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+pub(crate) fn avx2_available() -> bool {
+    is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma")
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+pub(crate) mod avx2 {
+    use std::arch::x86_64::*;
+    use super::Kernel;
+
+    /// `exp` of 8 lanes (Cephes `expf` polynomial, ~1 ulp on the clamped range).
+    /// Results underflow to subnormals and then 0 like `f32::exp`; above 88.72 (ln f32::MAX)
+    /// they are +inf. NaN propagates.
+    #[target_feature(enable = "avx2,fma")]
+    pub(crate) unsafe fn exp_ps(x: __m256) -> __m256 {
+        let overflow = _mm256_cmp_ps(x, _mm256_set1_ps(88.722_84), _CMP_GT_OQ);
+        // Constant first: min/max return the second operand when either is NaN.
+        // exp(-104) already rounds to 0.
+        let x = _mm256_min_ps(_mm256_set1_ps(88.722_84), x);
+        let x = _mm256_max_ps(_mm256_set1_ps(-104.0), x);
+
+        // exp(x) = 2^n * exp(r) with n = round(x / ln 2), r = x - n ln 2 (ln 2 split in two parts)
+        let n = _mm256_floor_ps(_mm256_fmadd_ps(x, _mm256_set1_ps(std::f32::consts::LOG2_E), _mm256_set1_ps(0.5)));
+        let r = _mm256_fnmadd_ps(n, _mm256_set1_ps(0.693_359_4), x);
+        let r = _mm256_fnmadd_ps(n, _mm256_set1_ps(-2.121_944_4e-4), r);
+
+        let mut p = _mm256_set1_ps(1.987_569_1e-4);
+        p = _mm256_fmadd_ps(p, r, _mm256_set1_ps(1.398_2e-3));
+        p = _mm256_fmadd_ps(p, r, _mm256_set1_ps(8.333_452e-3));
+        p = _mm256_fmadd_ps(p, r, _mm256_set1_ps(4.166_579_6e-2));
+        p = _mm256_fmadd_ps(p, r, _mm256_set1_ps(0.166_666_65));
+        p = _mm256_fmadd_ps(p, r, _mm256_set1_ps(0.5));
+        let r2 = _mm256_mul_ps(r, r);
+        let e = _mm256_add_ps(_mm256_fmadd_ps(p, r2, r), _mm256_set1_ps(1.0));
+
+        // 2^n built in the exponent bits, as 2^(n/2) * 2^(n - n/2) so that n in [-150, 128]
+        // never leaves the normal range
+        let n = _mm256_cvtps_epi32(n);
+        let half = _mm256_srai_epi32(n, 1);
+        let scale = |k: __m256i| _mm256_castsi256_ps(_mm256_slli_epi32(_mm256_add_epi32(k, _mm256_set1_epi32(127)), 23));
+        let y = _mm256_mul_ps(_mm256_mul_ps(e, scale(half)), scale(_mm256_sub_epi32(n, half)));
+        _mm256_blendv_ps(y, _mm256_set1_ps(f32::INFINITY), overflow)
+    }
+
+    /// Natural log of 8 lanes (Cephes `logf`, ~1 ulp for normal inputs).
+    /// Negative -> NaN, 0 -> -inf, +inf -> +inf; subnormals are treated as the smallest normal.
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn log_ps(x: __m256) -> __m256 {
+        let zero = _mm256_setzero_ps();
+        let one = _mm256_set1_ps(1.0);
+        let negative = _mm256_cmp_ps(x, zero, _CMP_LT_OQ);
+        let is_zero = _mm256_cmp_ps(x, zero, _CMP_EQ_OQ);
+        let is_inf = _mm256_cmp_ps(x, _mm256_set1_ps(f32::INFINITY), _CMP_EQ_OQ);
+        let is_nan = _mm256_cmp_ps(x, x, _CMP_UNORD_Q);
+
+        // x = m * 2^e with m in [0.5, 1)
+        let v = _mm256_max_ps(x, _mm256_castsi256_ps(_mm256_set1_epi32(0x0080_0000)));
+        let bits = _mm256_castps_si256(v);
+        let mut e = _mm256_cvtepi32_ps(_mm256_sub_epi32(_mm256_srli_epi32(bits, 23), _mm256_set1_epi32(126)));
+        let m = _mm256_castsi256_ps(_mm256_or_si256(
+            _mm256_and_si256(bits, _mm256_set1_epi32(!0x7f80_0000)),
+            _mm256_castps_si256(_mm256_set1_ps(0.5)),
+        ));
+
+        // Fold m into [sqrt(1/2), sqrt(2)) and take f = m - 1
+        let small = _mm256_cmp_ps(m, _mm256_set1_ps(std::f32::consts::FRAC_1_SQRT_2), _CMP_LT_OQ);
+        e = _mm256_sub_ps(e, _mm256_and_ps(one, small));
+        let f = _mm256_sub_ps(_mm256_add_ps(m, _mm256_and_ps(m, small)), one);
+
+        let z = _mm256_mul_ps(f, f);
+        let mut p = _mm256_set1_ps(7.037_683_6e-2);
+        for c in [-1.151_461e-1, 1.167_699_9e-1, -1.242_014_1e-1, 1.424_932_3e-1, -1.666_805_8e-1, 2.000_071_4e-1, -2.499_999_4e-1, 3.333_333e-1] {
+            p = _mm256_fmadd_ps(p, f, _mm256_set1_ps(c));
+        }
+        let mut y = _mm256_mul_ps(_mm256_mul_ps(p, f), z);
+        y = _mm256_fmadd_ps(e, _mm256_set1_ps(-2.121_944_4e-4), y);
+        y = _mm256_fnmadd_ps(_mm256_set1_ps(0.5), z, y);
+        let mut r = _mm256_add_ps(f, y);
+        r = _mm256_fmadd_ps(e, _mm256_set1_ps(0.693_359_4), r);
+
+        r = _mm256_blendv_ps(r, _mm256_set1_ps(f32::NEG_INFINITY), is_zero);
+        r = _mm256_blendv_ps(r, _mm256_set1_ps(f32::INFINITY), is_inf);
+        _mm256_blendv_ps(r, _mm256_set1_ps(f32::NAN), _mm256_or_ps(negative, is_nan))
+    }
+
+    /// Sine (`cos == false`) or cosine of 8 lanes (Cephes `sinf`/`cosf`).
+    /// Accurate to a few ulp for |x| up to ~8192; larger arguments lose precision in the reduction.
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn sincos_ps(x: __m256, cos: bool) -> __m256 {
+        let sign_mask = _mm256_set1_ps(-0.0);
+        let mut sign = if cos { _mm256_setzero_ps() } else { _mm256_and_ps(x, sign_mask) };
+        let a = _mm256_andnot_ps(sign_mask, x);
+
+        // Octant j (rounded up to even) and the reduced argument a - j * pi/4
+        let mut j = _mm256_cvttps_epi32(_mm256_mul_ps(a, _mm256_set1_ps(4.0 / std::f32::consts::PI)));
+        j = _mm256_and_si256(_mm256_add_epi32(j, _mm256_set1_epi32(1)), _mm256_set1_epi32(!1));
+        let y = _mm256_cvtepi32_ps(j);
+        let flip = if cos {
+            j = _mm256_sub_epi32(j, _mm256_set1_epi32(2));
+            _mm256_andnot_si256(j, _mm256_set1_epi32(4))
+        } else {
+            _mm256_and_si256(j, _mm256_set1_epi32(4))
+        };
+        sign = _mm256_xor_ps(sign, _mm256_castsi256_ps(_mm256_slli_epi32(flip, 29)));
+        let use_sin_poly = _mm256_castsi256_ps(_mm256_cmpeq_epi32(_mm256_and_si256(j, _mm256_set1_epi32(2)), _mm256_setzero_si256()));
+
+        let mut r = _mm256_fmadd_ps(y, _mm256_set1_ps(-0.785_156_25), a);
+        r = _mm256_fmadd_ps(y, _mm256_set1_ps(-2.418_756_5e-4), r);
+        r = _mm256_fmadd_ps(y, _mm256_set1_ps(-3.774_895e-8), r);
+        let z = _mm256_mul_ps(r, r);
+
+        let mut c = _mm256_set1_ps(2.443_315_7e-5);
+        c = _mm256_fmadd_ps(c, z, _mm256_set1_ps(-1.388_731_6e-3));
+        c = _mm256_fmadd_ps(c, z, _mm256_set1_ps(4.166_664_6e-2));
+        c = _mm256_mul_ps(_mm256_mul_ps(c, z), z);
+        c = _mm256_fnmadd_ps(_mm256_set1_ps(0.5), z, c);
+        c = _mm256_add_ps(c, _mm256_set1_ps(1.0));
+
+        let mut s = _mm256_set1_ps(-1.951_529_6e-4);
+        s = _mm256_fmadd_ps(s, z, _mm256_set1_ps(8.332_161e-3));
+        s = _mm256_fmadd_ps(s, z, _mm256_set1_ps(-1.666_665_5e-1));
+        s = _mm256_fmadd_ps(_mm256_mul_ps(s, z), r, r);
+
+        _mm256_xor_ps(_mm256_blendv_ps(c, s, use_sin_poly), sign)
+    }
+
+    /// `1 / sqrt(x)`: hardware estimate refined by one Newton step (~2e-7 relative error).
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn rsqrt_ps(x: __m256) -> __m256 {
+        let y = _mm256_rsqrt_ps(x);
+        let refined = _mm256_mul_ps(
+            _mm256_mul_ps(_mm256_set1_ps(0.5), y),
+            _mm256_fnmadd_ps(_mm256_mul_ps(x, y), y, _mm256_set1_ps(3.0)),
+        );
+        // The Newton step turns 0 -> inf and inf -> 0 into NaN; keep the estimate there
+        let edge = _mm256_or_ps(
+            _mm256_cmp_ps(x, _mm256_setzero_ps(), _CMP_EQ_OQ),
+            _mm256_cmp_ps(x, _mm256_set1_ps(f32::INFINITY), _CMP_EQ_OQ),
+        );
+        _mm256_blendv_ps(refined, y, edge)
+    }
+
+    /// `1 / (1 + exp(-x))`.
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn sigmoid_ps(x: __m256) -> __m256 {
+        let one = _mm256_set1_ps(1.0);
+        _mm256_div_ps(one, _mm256_add_ps(one, exp_ps(_mm256_sub_ps(_mm256_setzero_ps(), x))))
+    }
+
+    /// Cephes `tanhf`: an odd polynomial below |x| = 0.625, where `2 * sigmoid(2x) - 1` would
+    /// cancel, and that form above it. Within a few ulp everywhere.
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn tanh_ps(x: __m256) -> __m256 {
+        let small = _mm256_cmp_ps(_mm256_andnot_ps(_mm256_set1_ps(-0.0), x), _mm256_set1_ps(0.625), _CMP_LT_OQ);
+
+        let z = _mm256_mul_ps(x, x);
+        let mut p = _mm256_set1_ps(-5.704_988_7e-3);
+        p = _mm256_fmadd_ps(p, z, _mm256_set1_ps(2.063_909e-2));
+        p = _mm256_fmadd_ps(p, z, _mm256_set1_ps(-5.373_971_6e-2));
+        p = _mm256_fmadd_ps(p, z, _mm256_set1_ps(1.333_144_2e-1));
+        p = _mm256_fmadd_ps(p, z, _mm256_set1_ps(-3.333_328e-1));
+        let poly = _mm256_fmadd_ps(_mm256_mul_ps(p, z), x, x);
+
+        let two = _mm256_set1_ps(2.0);
+        let large = _mm256_fmsub_ps(two, sigmoid_ps(_mm256_mul_ps(two, x)), _mm256_set1_ps(1.0));
+        _mm256_blendv_ps(large, poly, small)
+    }
+
+    /// `ln(1 + exp(beta * x)) / beta`, or `x` once `beta * x > 20`, like the scalar `softplus`.
+    /// `ln(1 + e)` is evaluated as `ln(u) * e / (u - 1)` with `u = 1 + e`, which cancels the
+    /// rounding of `u` and keeps the negative tail accurate to a few ulp.
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn softplus_ps(x: __m256, beta: f32) -> __m256 {
+        let one = _mm256_set1_ps(1.0);
+        let bx = _mm256_mul_ps(_mm256_set1_ps(beta), x);
+        let e = exp_ps(bx);
+        let u = _mm256_add_ps(one, e);
+        let log1p = _mm256_div_ps(_mm256_mul_ps(log_ps(u), e), _mm256_sub_ps(u, one));
+        let log1p = _mm256_blendv_ps(log1p, e, _mm256_cmp_ps(u, one, _CMP_EQ_OQ));
+        let y = _mm256_div_ps(log1p, _mm256_set1_ps(beta));
+        _mm256_blendv_ps(y, x, _mm256_cmp_ps(bx, _mm256_set1_ps(20.0), _CMP_GT_OQ))
+    }
+
+    /// Abramowitz & Stegun 7.1.26, absolute error < 1.5e-7. Same formula as the scalar `erf`.
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn erf_ps(x: __m256) -> __m256 {
+        let sign = _mm256_and_ps(x, _mm256_set1_ps(-0.0));
+        let a = _mm256_andnot_ps(_mm256_set1_ps(-0.0), x);
+        let one = _mm256_set1_ps(1.0);
+        let t = _mm256_div_ps(one, _mm256_fmadd_ps(_mm256_set1_ps(0.327_591_1), a, one));
+
+        let mut p = _mm256_set1_ps(1.061_405_4);
+        p = _mm256_fmadd_ps(p, t, _mm256_set1_ps(-1.453_152_1));
+        p = _mm256_fmadd_ps(p, t, _mm256_set1_ps(1.421_413_8));
+        p = _mm256_fmadd_ps(p, t, _mm256_set1_ps(-0.284_496_72));
+        p = _mm256_fmadd_ps(p, t, _mm256_set1_ps(0.254_829_6));
+        p = _mm256_mul_ps(p, t);
+
+        let e = exp_ps(_mm256_sub_ps(_mm256_setzero_ps(), _mm256_mul_ps(a, a)));
+        let y = _mm256_fnmadd_ps(p, e, one);
+        _mm256_or_ps(y, sign)
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn eval(kernel: Kernel, x: __m256) -> __m256 {
+        let half = _mm256_set1_ps(0.5);
+        match kernel {
+            Kernel::Sigmoid => sigmoid_ps(x),
+            Kernel::Tanh => tanh_ps(x),
+            Kernel::Silu => _mm256_mul_ps(x, sigmoid_ps(x)),
+            Kernel::Gelu => {
+                let e = erf_ps(_mm256_mul_ps(x, _mm256_set1_ps(std::f32::consts::FRAC_1_SQRT_2)));
+                _mm256_mul_ps(_mm256_mul_ps(half, x), _mm256_add_ps(_mm256_set1_ps(1.0), e))
+            }
+            Kernel::GeluTanh => {
+                // 0.5 x (1 + tanh(u)) == x * sigmoid(2u), u = sqrt(2/pi) (x + 0.044715 x^3)
+                let x3 = _mm256_mul_ps(_mm256_mul_ps(x, x), x);
+                let u = _mm256_mul_ps(_mm256_set1_ps(0.797_884_6), _mm256_fmadd_ps(_mm256_set1_ps(0.044_715), x3, x));
+                _mm256_mul_ps(x, sigmoid_ps(_mm256_add_ps(u, u)))
+            }
+            Kernel::Softplus(beta) => softplus_ps(x, beta),
+            // NaN fails the comparison and takes `slope * x`, like the scalar form
+            Kernel::LeakyRelu(slope) => {
+                let positive = _mm256_cmp_ps(x, _mm256_setzero_ps(), _CMP_GT_OQ);
+                _mm256_blendv_ps(_mm256_mul_ps(_mm256_set1_ps(slope), x), x, positive)
+            }
+            Kernel::Exp => exp_ps(x),
+            Kernel::Log => log_ps(x),
+            Kernel::Sin => sincos_ps(x, false),
+            Kernel::Cos => sincos_ps(x, true),
+            Kernel::Rsqrt => rsqrt_ps(x),
+            Kernel::Sqrt => _mm256_sqrt_ps(x),
+            Kernel::Abs => _mm256_andnot_ps(_mm256_set1_ps(-0.0), x),
+            Kernel::Neg => _mm256_xor_ps(_mm256_set1_ps(-0.0), x),
+            Kernel::Reciprocal => _mm256_div_ps(_mm256_set1_ps(1.0), x),
+            // Bounds first so NaN inputs pass through, like `f32::clamp`
+            Kernel::Clamp(lo, hi) => _mm256_min_ps(_mm256_set1_ps(hi), _mm256_max_ps(_mm256_set1_ps(lo), x)),
+        }
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub(crate) unsafe fn map_dense(kernel: Kernel, src: &[f32], dst: &mut [f32]) {
+        let n = src.len();
+        let body = n - n % 8;
+        for i in (0..body).step_by(8) {
+            _mm256_storeu_ps(dst.as_mut_ptr().add(i), eval(kernel, _mm256_loadu_ps(src.as_ptr().add(i))));
+        }
+        if body < n {
+            // Tail through a padded register, so every element sees the same approximation
+            let mut buf = [0.0f32; 8];
+            buf[..n - body].copy_from_slice(&src[body..]);
+            _mm256_storeu_ps(buf.as_mut_ptr(), eval(kernel, _mm256_loadu_ps(buf.as_ptr())));
+            dst[body..].copy_from_slice(&buf[..n - body]);
+        }
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub(crate) unsafe fn hsum(v: __m256) -> f32 {
+        let s = _mm_add_ps(_mm256_castps256_ps128(v), _mm256_extractf128_ps(v, 1));
+        let s = _mm_add_ps(s, _mm_movehl_ps(s, s));
+        _mm_cvtss_f32(_mm_add_ss(s, _mm_shuffle_ps(s, s, 1)))
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub(crate) unsafe fn hmax(v: __m256) -> f32 {
+        let m = _mm_max_ps(_mm256_castps256_ps128(v), _mm256_extractf128_ps(v, 1));
+        let m = _mm_max_ps(m, _mm_movehl_ps(m, m));
+        _mm_cvtss_f32(_mm_max_ss(m, _mm_shuffle_ps(m, m, 1)))
+    }
+
+    /// Softmax (or log-softmax) of one contiguous row. Returns `false` without writing
+    /// when the row max is not finite, leaving that row to the scalar reference.
+    #[target_feature(enable = "avx2,fma")]
+    pub(crate) unsafe fn softmax_row(src: &[f32], dst: &mut [f32], log: bool) -> bool {
+        let n = src.len();
+        let body = n - n % 8;
+        let (s, d) = (src.as_ptr(), dst.as_mut_ptr());
+
+        let mut vmax = _mm256_set1_ps(f32::NEG_INFINITY);
+        for i in (0..body).step_by(8) {
+            vmax = _mm256_max_ps(vmax, _mm256_loadu_ps(s.add(i)));
+        }
+        let max = src[body..].iter().fold(hmax(vmax), |m, &x| m.max(x));
+        if !max.is_finite() {
+            return false;
+        }
+
+        // Pass 2: exp(x - max) and its sum; softmax keeps the exponentials in `dst`
+        let vm = _mm256_set1_ps(max);
+        let mut vsum = _mm256_setzero_ps();
+        for i in (0..body).step_by(8) {
+            let e = exp_ps(_mm256_sub_ps(_mm256_loadu_ps(s.add(i)), vm));
+            if !log {
+                _mm256_storeu_ps(d.add(i), e);
+            }
+            vsum = _mm256_add_ps(vsum, e);
+        }
+        let mut sum = hsum(vsum);
+        for i in body..n {
+            let e = (src[i] - max).exp();
+            if !log {
+                dst[i] = e;
+            }
+            sum += e;
+        }
+
+        // Pass 3: normalize
+        if log {
+            let lse = sum.ln();
+            let vl = _mm256_set1_ps(lse);
+            for i in (0..body).step_by(8) {
+                _mm256_storeu_ps(d.add(i), _mm256_sub_ps(_mm256_sub_ps(_mm256_loadu_ps(s.add(i)), vm), vl));
+            }
+            for i in body..n {
+                dst[i] = (src[i] - max) - lse;
+            }
+        } else {
+            let inv = sum.recip();
+            let vi = _mm256_set1_ps(inv);
+            for i in (0..body).step_by(8) {
+                _mm256_storeu_ps(d.add(i), _mm256_mul_ps(_mm256_loadu_ps(d.add(i)), vi));
+            }
+            for x in &mut dst[body..] {
+                *x *= inv;
+            }
+        }
+        true
+    }
+
+    /// GEMM micro-kernel: the 6 x 16 tile of `A_panel B_panel` over depth `kc`, held in twelve
+    /// ymm accumulators. Panels are packed by `ops::gemm` (6 A values, then 16 B values, per step).
+    /// The tile is stored to `c` with row stride `ldc`, or added to it when `accumulate`.
+    #[target_feature(enable = "avx2,fma")]
+    pub(crate) unsafe fn gemm_6x16(kc: usize, a: *const f32, b: *const f32, c: *mut f32, ldc: usize, accumulate: bool) {
+        use crate::ops::gemm::{MR, NR};
+        let mut acc = [[_mm256_setzero_ps(); 2]; MR];
+        for p in 0..kc {
+            let b0 = _mm256_loadu_ps(b.add(p * NR));
+            let b1 = _mm256_loadu_ps(b.add(p * NR + 8));
+            for (i, row) in acc.iter_mut().enumerate() {
+                let av = _mm256_broadcast_ss(&*a.add(p * MR + i));
+                row[0] = _mm256_fmadd_ps(av, b0, row[0]);
+                row[1] = _mm256_fmadd_ps(av, b1, row[1]);
+            }
+        }
+        for (i, row) in acc.iter().enumerate() {
+            let out = c.add(i * ldc);
+            for (h, &v) in row.iter().enumerate() {
+                let dst = out.add(h * 8);
+                let v = if accumulate { _mm256_add_ps(_mm256_loadu_ps(dst), v) } else { v };
+                _mm256_storeu_ps(dst, v);
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::sync::Arc;
+use rayon::prelude::*;
+use crate::autograd::grad_mode::needs_grad;
+use crate::autograd::node::Node;
+use crate::error::{Result, TensorError};
+use crate::ops::binary::{mul, sub};
+use crate::ops::precision::{precision, Precision};
+use crate::ops::reduce::sum;
+use crate::ops::strided::for_each_offset;
+use crate::parallel::{install, should_parallelize};
+use crate::tensor::{DType, Tensor};
+
+// Softmax along one dim, stabilized by subtracting each lane's max before `exp`.
+// Contiguous last-dim inputs (attention scores) take a row kernel with an AVX2 path;
+// any other layout walks strided lanes with the scalar reference.
+
+/// Calls `f(bases)` once per lane along `dim`, with each operand's offset of the lane's first element.
+fn for_each_lane<const N: usize>(shape: &[usize], dim: usize, strides: [&[usize]; N], mut f: impl FnMut([usize; N])) {
+    let mut outer = shape.to_vec();
+    outer.remove(dim);
+    let outer_strides: [Vec<usize>; N] = std::array::from_fn(|k| {
+        let mut s = strides[k].to_vec();
+        s.remove(dim);
+        s
+    });
+    let refs: [&[usize]; N] = std::array::from_fn(|k| outer_strides[k].as_slice());
+    for_each_offset(&outer, refs, |_, bases| f(bases));
+}
+
+/// Scalar reference: softmax (or log-softmax) of `len` values at `src[i * s]`, written to `dst[i * d]`.
+unsafe fn softmax_lane(src: *const f32, s: usize, dst: *mut f32, d: usize, len: usize, log: bool) {
+    let max = (0..len).map(|i| *src.add(i * s)).fold(f32::NEG_INFINITY, f32::max);
+    let mut total = 0.0;
+    for i in 0..len {
+        let e = (*src.add(i * s) - max).exp();
+        if !log {
+            *dst.add(i * d) = e;
+        }
+        total += e;
+    }
+    if log {
+        // Subtract in two steps: folding `max + ln(total)` first would round away small logits
+        let lse = total.ln();
+        for i in 0..len {
+            *dst.add(i * d) = (*src.add(i * s) - max) - lse;
+        }
+    } else {
+        let inv = total.recip();
+        for i in 0..len {
+            *dst.add(i * d) *= inv;
+        }
+    }
+}
+
+/// `fast` allows the AVX2 row kernel, whose `exp` is approximate. It is read once by the
+/// caller: rows may run on rayon workers, which don't share the caller's thread-local precision.
+fn softmax_row(src: &[f32], dst: &mut [f32], log: bool, fast: bool) {
+    #[cfg(all(target_arch = "x86_64", feature = "avx2"))]
+    if fast
+        && crate::ops::simd::avx2_available()
+        && unsafe { crate::ops::simd::avx2::softmax_row(src, dst, log) }
+    {
+        return;
+    }
+    let _ = fast;
+    unsafe { softmax_lane(src.as_ptr(), 1, dst.as_mut_ptr(), 1, src.len(), log) }
+}
+
+fn softmax_forward(input: &Tensor, dim: usize, log: bool) -> Tensor {
+    let output = Tensor::zeros(input.shape.clone(), DType::F32);
+    let len = input.shape[dim];
+    let numel = input.numel();
+    if numel == 0 {
+        return output;
+    }
+
+    unsafe {
+        let in_ptr = input.storage.as_ptr().add(input.offset) as *const f32;
+        let out_ptr = output.storage.as_ptr() as *mut f32;
+
+        if dim == input.shape.len() - 1 && input.is_contiguous() {
+            let src = std::slice::from_raw_parts(in_ptr, numel);
+            let dst = std::slice::from_raw_parts_mut(out_ptr, numel);
+            let fast = precision() == Precision::Fast;
+            if should_parallelize(numel) {
+                install(|| src.par_chunks(len).zip(dst.par_chunks_mut(len)).for_each(|(s, d)| softmax_row(s, d, log, fast)));
+            } else {
+                src.chunks(len).zip(dst.chunks_mut(len)).for_each(|(s, d)| softmax_row(s, d, log, fast));
+            }
+        } else {
+            let dense = Tensor::default_strides(&input.shape);
+            let (s, d) = (input.strides[dim], dense[dim]);
+            for_each_lane(&input.shape, dim, [&input.strides, &dense], |[a, o]| {
+                softmax_lane(in_ptr.add(a), s, out_ptr.add(o), d, len, log);
+            });
+        }
+    }
+
+    output
+}
+
+/// Fused vector-Jacobian product for a softmax output `y`:
+/// softmax: `y * (g - sum(g * y))`, log-softmax: `g - exp(y) * sum(g)`, sums taken per lane.
+fn softmax_backward(grad: &Tensor, y: &Tensor, dim: usize, log: bool) -> Tensor {
+    let output = Tensor::zeros(y.shape.clone(), DType::F32);
+    let dense = Tensor::default_strides(&y.shape);
+    let len = y.shape[dim];
+    let (gs, ys, os) = (grad.strides[dim], y.strides[dim], dense[dim]);
+
+    unsafe {
+        let g_ptr = grad.storage.as_ptr().add(grad.offset) as *const f32;
+        let y_ptr = y.storage.as_ptr().add(y.offset) as *const f32;
+        let out_ptr = output.storage.as_ptr() as *mut f32;
+
+        for_each_lane(&y.shape, dim, [&grad.strides, &y.strides, &dense], |[g, yb, o]| {
+            let (g, yv, out) = (g_ptr.add(g), y_ptr.add(yb), out_ptr.add(o));
+            if log {
+                let total: f32 = (0..len).map(|i| *g.add(i * gs)).sum();
+                for i in 0..len {
+                    *out.add(i * os) = *g.add(i * gs) - (*yv.add(i * ys)).exp() * total;
+                }
+            } else {
+                let dot: f32 = (0..len).map(|i| *g.add(i * gs) * *yv.add(i * ys)).sum();
+                for i in 0..len {
+                    *out.add(i * os) = *yv.add(i * ys) * (*g.add(i * gs) - dot);
+                }
+            }
+        });
+    }
+
+    output
+}
+
+#[derive(Debug)]
+pub struct SoftmaxNode {
+    input: Tensor,
+    output: Tensor, // Detached result
+    dim: usize,
+}
+
+impl Node for SoftmaxNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // When recording (create_graph) the output is recomputed from the input with recorded
+        // ops, so second derivatives flow through it
+        if needs_grad(&[grad, &self.input]) {
+            let y = softmax(&self.input, self.dim);
+            let dot = sum(&mul(grad, &y), Some(self.dim), true);
+            return vec![mul(&y, &sub(grad, &dot))];
+        }
+        vec![softmax_backward(grad, &self.output, self.dim, false)]
+    }
+}
+
+#[derive(Debug)]
+pub struct LogSoftmaxNode {
+    input: Tensor,
+    output: Tensor, // Detached result
+    dim: usize,
+}
+
+impl Node for LogSoftmaxNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // When recording (create_graph) the probabilities are recomputed from the input with
+        // recorded ops, so second derivatives flow through them
+        if needs_grad(&[grad, &self.input]) {
+            let probs = softmax(&self.input, self.dim);
+            return vec![sub(grad, &mul(&probs, &sum(grad, Some(self.dim), true)))];
+        }
+        vec![softmax_backward(grad, &self.output, self.dim, true)]
+    }
+}
+
+fn check_softmax(input: &Tensor, dim: usize) -> Result<()> {
+    if input.dtype != DType::F32 {
+        return Err(TensorError::Unsupported(format!("softmax on {:?}", input.dtype)));
+    }
+    if dim >= input.shape.len() {
+        return Err(TensorError::OutOfBounds { index: vec![dim], shape: input.shape.clone() });
+    }
+    Ok(())
+}
+
+/// `exp(x - max) / sum(exp(x - max))` along `dim`.
+pub fn try_softmax(input: &Tensor, dim: usize) -> Result<Tensor> {
+    check_softmax(input, dim)?;
+    let output = softmax_forward(input, dim, false);
+
+    if needs_grad(&[input]) {
+        let saved = output.detach();
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(SoftmaxNode { input: input.clone(), output: saved, dim }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_softmax`].
+pub fn softmax(input: &Tensor, dim: usize) -> Tensor {
+    try_softmax(input, dim).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// `x - max - ln(sum(exp(x - max)))` along `dim`; finite even where softmax underflows to 0.
+pub fn try_log_softmax(input: &Tensor, dim: usize) -> Result<Tensor> {
+    check_softmax(input, dim)?;
+    let output = softmax_forward(input, dim, true);
+
+    if needs_grad(&[input]) {
+        let saved = output.detach();
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(Arc::new(LogSoftmaxNode { input: input.clone(), output: saved, dim }));
+        return Ok(out);
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_log_softmax`].
+pub fn log_softmax(input: &Tensor, dim: usize) -> Tensor {
+    try_log_softmax(input, dim).unwrap_or_else(|e| panic!("{}", e))
+}
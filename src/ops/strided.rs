@@ -0,0 +1,209 @@
+use std::ops::Range;
+use half::f16;
+use rayon::prelude::*;
+use crate::parallel::{install, should_parallelize, SyncPtr};
+use crate::error::{Result, TensorError};
+use crate::tensor::{Tensor, DType};
+
+// Strided iteration engine shared by every elementwise kernel.
+// Kernels describe each operand by (base pointer, element strides) over a common shape
+// and never assume a dense layout, so transposed, sliced and broadcast views all work.
+
+/// Elements walked per task by [`par_for_each_offset`].
+const CHUNK: usize = 1 << 13;
+
+/// Walks `shape` in row-major order and calls `f(i, offsets)` with the linear index and the
+/// element offset of each of the `N` operands described by `strides`.
+///
+/// Takes a flat loop when every operand is contiguous, otherwise steps an N-d index
+/// like an odometer with the innermost dim unrolled into a tight loop.
+pub(crate) fn for_each_offset<const N: usize>(
+    shape: &[usize],
+    strides: [&[usize]; N],
+    mut f: impl FnMut(usize, [usize; N]),
+) {
+    let numel: usize = shape.iter().product();
+    for_each_offset_in(shape, strides, 0..numel, &mut f);
+}
+
+/// [`for_each_offset`] split into chunks of the linear index range that run on the engine's
+/// pool when the shape is large. `f` must only write locations owned by its own index.
+pub(crate) fn par_for_each_offset<const N: usize>(
+    shape: &[usize],
+    strides: [&[usize]; N],
+    f: impl Fn(usize, [usize; N]) + Sync,
+) {
+    let numel: usize = shape.iter().product();
+    if !should_parallelize(numel) {
+        return for_each_offset_in(shape, strides, 0..numel, &mut |i, o| f(i, o));
+    }
+    install(|| {
+        (0..numel.div_ceil(CHUNK)).into_par_iter().for_each(|c| {
+            let range = c * CHUNK..((c + 1) * CHUNK).min(numel);
+            for_each_offset_in(shape, strides, range, &mut |i, o| f(i, o));
+        })
+    });
+}
+
+/// Walks the linear indices in `range` of the row-major order of `shape`.
+fn for_each_offset_in<const N: usize>(
+    shape: &[usize],
+    strides: [&[usize]; N],
+    range: Range<usize>,
+    f: &mut impl FnMut(usize, [usize; N]),
+) {
+    if range.is_empty() {
+        return;
+    }
+
+    // Fast path: every operand is dense row-major with the same shape
+    let dense = Tensor::default_strides(shape);
+    if strides.iter().all(|s| is_dense(shape, s, &dense)) {
+        for i in range {
+            f(i, [i; N]);
+        }
+        return;
+    }
+
+    let ndim = shape.len();
+    let inner = shape[ndim - 1];
+    let inner_strides: [usize; N] = std::array::from_fn(|k| strides[k][ndim - 1]);
+
+    // Unravel the start of the range into an outer index and a position within the innermost dim
+    let mut index = vec![0; ndim - 1];
+    let mut rest = range.start / inner;
+    for d in (0..ndim - 1).rev() {
+        index[d] = rest % shape[d];
+        rest /= shape[d];
+    }
+    let mut base: [usize; N] = std::array::from_fn(|k| index.iter().zip(strides[k]).map(|(i, s)| i * s).sum());
+    let mut j = range.start % inner;
+    let mut i = range.start;
+
+    loop {
+        let mut offsets: [usize; N] = std::array::from_fn(|k| base[k] + j * inner_strides[k]);
+        let stop = inner.min(j + range.end - i);
+        for _ in j..stop {
+            f(i, offsets);
+            i += 1;
+            for k in 0..N {
+                offsets[k] += inner_strides[k];
+            }
+        }
+        if i == range.end {
+            return;
+        }
+        j = 0;
+
+        // Advance the outer dims
+        let mut d = ndim - 1;
+        loop {
+            if d == 0 {
+                return;
+            }
+            d -= 1;
+            index[d] += 1;
+            for k in 0..N {
+                base[k] += strides[k][d];
+            }
+            if index[d] < shape[d] {
+                break;
+            }
+            for k in 0..N {
+                base[k] -= strides[k][d] * shape[d];
+            }
+            index[d] = 0;
+        }
+    }
+}
+
+/// Size-1 dims may carry any stride without changing the layout.
+fn is_dense(shape: &[usize], strides: &[usize], dense: &[usize]) -> bool {
+    shape.iter().zip(strides.iter().zip(dense.iter())).all(|(&dim, (&s, &d))| dim == 1 || s == d)
+}
+
+/// The dtypes elementwise kernels compute on.
+fn check_float(dtype: DType) -> Result<()> {
+    if !matches!(dtype, DType::F32 | DType::F16) {
+        return Err(TensorError::Unsupported(format!("elementwise ops on {:?}", dtype)));
+    }
+    Ok(())
+}
+
+/// Applies `f` to every element of `input`, producing a fresh contiguous tensor.
+/// F16 is computed in F32 and rounded once per element; other dtypes are `Unsupported`.
+pub(crate) fn try_map_unary(input: &Tensor, f: impl Fn(f32) -> f32 + Sync) -> Result<Tensor> {
+    check_float(input.dtype)?;
+    let output = Tensor::zeros(input.shape.clone(), input.dtype);
+
+    unsafe {
+        let in_ptr = input.storage.as_ptr().add(input.offset);
+        let out_ptr = output.storage.as_ptr();
+        match input.dtype {
+            DType::F32 => {
+                let (in_ptr, out_ptr) = (SyncPtr(in_ptr as *mut f32), SyncPtr(out_ptr as *mut f32));
+                par_for_each_offset(&input.shape, [&input.strides], |i, [x]| {
+                    *out_ptr.get().add(i) = f(*in_ptr.get().add(x));
+                });
+            }
+            DType::F16 => {
+                let (in_ptr, out_ptr) = (SyncPtr(in_ptr as *mut f16), SyncPtr(out_ptr as *mut f16));
+                par_for_each_offset(&input.shape, [&input.strides], |i, [x]| {
+                    *out_ptr.get().add(i) = f16::from_f32(f((*in_ptr.get().add(x)).to_f32()));
+                });
+            }
+            _ => unreachable!("checked above"),
+        }
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_map_unary`], for operands already validated by the caller.
+pub(crate) fn map_unary(input: &Tensor, f: impl Fn(f32) -> f32 + Sync) -> Tensor {
+    try_map_unary(input, f).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Applies `f` elementwise with NumPy broadcasting, producing a fresh contiguous tensor.
+/// F16 is computed in F32 and rounded once per element; other dtypes are `Unsupported`.
+pub(crate) fn try_map_binary(lhs: &Tensor, rhs: &Tensor, f: impl Fn(f32, f32) -> f32 + Sync) -> Result<Tensor> {
+    if lhs.dtype != rhs.dtype {
+        return Err(TensorError::DTypeMismatch { expected: lhs.dtype, found: rhs.dtype });
+    }
+    check_float(lhs.dtype)?;
+    let shape = Tensor::broadcast_shape(&lhs.shape, &rhs.shape)
+        .ok_or_else(|| TensorError::IncompatibleShapes { lhs: lhs.shape.clone(), rhs: rhs.shape.clone() })?;
+
+    let output = Tensor::zeros(shape.clone(), lhs.dtype);
+
+    // Stride-0 views let both operands be walked with the output's shape
+    let a_strides = lhs.broadcast_strides(&shape);
+    let b_strides = rhs.broadcast_strides(&shape);
+    unsafe {
+        let a_ptr = lhs.storage.as_ptr().add(lhs.offset);
+        let b_ptr = rhs.storage.as_ptr().add(rhs.offset);
+        let c_ptr = output.storage.as_ptr();
+        match lhs.dtype {
+            DType::F32 => {
+                let (a_ptr, b_ptr, c_ptr) = (SyncPtr(a_ptr as *mut f32), SyncPtr(b_ptr as *mut f32), SyncPtr(c_ptr as *mut f32));
+                par_for_each_offset(&shape, [&a_strides, &b_strides], |i, [a, b]| {
+                    *c_ptr.get().add(i) = f(*a_ptr.get().add(a), *b_ptr.get().add(b));
+                });
+            }
+            DType::F16 => {
+                let (a_ptr, b_ptr, c_ptr) = (SyncPtr(a_ptr as *mut f16), SyncPtr(b_ptr as *mut f16), SyncPtr(c_ptr as *mut f16));
+                par_for_each_offset(&shape, [&a_strides, &b_strides], |i, [a, b]| {
+                    *c_ptr.get().add(i) = f16::from_f32(f((*a_ptr.get().add(a)).to_f32(), (*b_ptr.get().add(b)).to_f32()));
+                });
+            }
+            _ => unreachable!("checked above"),
+        }
+    }
+
+    Ok(output)
+}
+
+/// Panicking form of [`try_map_binary`], for operands already validated by the caller.
+pub(crate) fn map_binary(lhs: &Tensor, rhs: &Tensor, f: impl Fn(f32, f32) -> f32 + Sync) -> Tensor {
+    try_map_binary(lhs, rhs, f).unwrap_or_else(|e| panic!("{}", e))
+}
@@ -1,6 +1,21 @@
 mod tests {
-    use crate::tensor::Tensor;
-    use crate::ops::matmul::matmul;
+    use crate::tensor::{Tensor, DType};
+    use crate::ops::matmul::{matmul, matmul_int4, try_matmul, try_matmul_int4};
+    use crate::ops::binary::{try_add, try_add_scalar, try_sub_scalar, try_mul_scalar, try_div_scalar, try_pow_scalar};
+    use crate::TensorError;
+    use crate::nn::attention_rope::rope;
+    use crate::ops::binary::{add, sub, mul, div, pow, maximum, minimum, mul_scalar, sub_scalar, pow_scalar};
+    use crate::ops::unary::{relu, leaky_relu, sigmoid, tanh, silu, gelu, gelu_tanh, softplus};
+    use crate::ops::unary::{exp, log, sqrt, rsqrt, abs, neg, sin, cos, clamp, reciprocal};
+    use crate::ops::precision::{precision, with_precision, Precision};
+    use crate::ops::strided::for_each_offset;
+    use crate::nn::linear::Linear;
+    use crate::ops::softmax::{softmax, log_softmax, try_softmax};
+    use crate::ops::reduce::{sum, mean, max, min, prod, argmax, argmin, try_sum, try_max};
+
+    fn values(t: &Tensor) -> Vec<f32> {
+        t.to_vec_f32().unwrap()
+    }
 
     #[test]
     fn test_matmul_f32() {
@@ -10,12 +25,673 @@ mod tests {
         
         let c = matmul(&a, &b);
         
+        assert_eq!(values(&c), vec![1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_gemm_matches_reference() {
+        use crate::ops::gemm::{gemm_reference, MatRef};
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let mut random = |shape: Vec<usize>| {
+            let data = (0..shape.iter().product()).map(|_| rng.gen_range(-1.0..1.0)).collect();
+            Tensor::from_vec_f32(data, shape)
+        };
+        // Shapes around the tile (6 x 16) and block (MC = 96, KC = 256, NC = 1024) edges
+        for (m, k, n) in [(1, 1, 1), (6, 300, 16), (7, 257, 17), (97, 40, 33), (13, 5, 1030), (2, 3000, 2)] {
+            for (transpose_a, transpose_b) in [(false, false), (true, false), (false, true), (true, true)] {
+                let a = if transpose_a { random(vec![k, m]).t() } else { random(vec![m, k]) };
+                let b = if transpose_b { random(vec![n, k]).t() } else { random(vec![k, n]) };
+                let got = values(&matmul(&a, &b));
+
+                let mut expected = vec![0.0f32; m * n];
+                unsafe {
+                    let a_ref = MatRef { ptr: a.storage.as_ptr() as *const f32, rs: a.strides[0], cs: a.strides[1] };
+                    let b_ref = MatRef { ptr: b.storage.as_ptr() as *const f32, rs: b.strides[0], cs: b.strides[1] };
+                    gemm_reference(m, n, k, a_ref, b_ref, expected.as_mut_ptr(), n);
+                }
+                // Summation order differs from the reference; errors grow like sqrt(k)
+                let tol = 1e-6 * (k as f32).sqrt() * 4.0;
+                for (idx, (g, e)) in got.iter().zip(&expected).enumerate() {
+                    assert!((g - e).abs() <= tol * (1.0 + e.abs()), "{}x{}x{} ({}, {}) at {}: {} vs {}", m, k, n, transpose_a, transpose_b, idx, g, e);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_batched_matmul() {
+        let data = |n: usize, seed: usize| (0..n).map(|i| (((i + seed) * 7919) % 200) as f32 / 100.0 - 1.0).collect::<Vec<f32>>();
+        let tensor = |shape: Vec<usize>, seed: usize| Tensor::from_vec_f32(data(shape.iter().product(), seed), shape);
+
+        // Naive reference: broadcast both operands densely, then one triple loop per matrix
+        let reference = |a: &Tensor, b: &Tensor| -> (Vec<usize>, Vec<f32>) {
+            let (m, k, n) = (a.shape()[a.shape().len() - 2], a.shape()[a.shape().len() - 1], b.shape()[b.shape().len() - 1]);
+            let batch = Tensor::broadcast_shape(&a.shape()[..a.shape().len() - 2], &b.shape()[..b.shape().len() - 2]).unwrap();
+            let a = values(&a.expand(&[batch.as_slice(), &[m, k]].concat()));
+            let b = values(&b.expand(&[batch.as_slice(), &[k, n]].concat()));
+            let mut out = vec![0.0; batch.iter().product::<usize>() * m * n];
+            for (bi, c) in out.chunks_mut(m * n).enumerate() {
+                for i in 0..m {
+                    for j in 0..n {
+                        c[i * n + j] = (0..k).map(|p| a[bi * m * k + i * k + p] * b[bi * k * n + p * n + j]).sum();
+                    }
+                }
+            }
+            ([batch.as_slice(), &[m, n]].concat(), out)
+        };
+
+        let q = tensor(vec![2, 3, 4, 5], 1);
+        let cases = vec![
+            // Same batch dims, and attention scores against a transposed view of K
+            (q.clone(), tensor(vec![2, 3, 5, 6], 2)),
+            (q.clone(), tensor(vec![2, 3, 4, 5], 3).t()),
+            // Broadcast: size-1 batch dims, missing leading dims, and a shared 2-D weight
+            (tensor(vec![3, 1, 4, 5], 4), tensor(vec![2, 5, 6], 5)),
+            (q.clone(), tensor(vec![6, 5], 6).t()),
+            (tensor(vec![4, 5], 7), tensor(vec![2, 1, 5, 3], 8)),
+            // Large enough to split the batch across the pool
+            (tensor(vec![8, 2, 40, 64], 9), tensor(vec![8, 1, 40, 64], 10).t()),
+        ];
+        for (a, b) in &cases {
+            let c = matmul(a, b);
+            let (shape, expected) = reference(a, b);
+            assert_eq!(c.shape(), shape.as_slice());
+            assert_close(&values(&c), &expected, 1e-5);
+        }
+
+        // Linear layers apply to any leading dims
+        let layer = Linear::new(5, 3, true);
+        assert_eq!(layer.forward(&q).shape(), &[2, 3, 4, 3]);
+
+        assert!(matches!(try_matmul(&q, &tensor(vec![3, 3, 5, 6], 0)), Err(TensorError::IncompatibleShapes { .. })));
+        assert!(matches!(try_matmul(&q, &tensor(vec![2, 3, 4, 6], 0)), Err(TensorError::ShapeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_matmul_views_with_offsets() {
+        let data = |n: usize| (0..n).map(|i| ((i * 37) % 23) as f32 / 10.0 - 1.0).collect::<Vec<f32>>();
+
+        // Rows 3..5 of a larger buffer: contiguous, but starting at a non-zero offset
+        let big = Tensor::from_vec_f32(data(8 * 4), vec![8, 4]);
+        let rows = big.narrow(0, 3, 2);
+        let copy = Tensor::from_vec_f32(values(&rows), vec![2, 4]);
+        let w = Tensor::from_vec_f32(data(4 * 3), vec![4, 3]);
+        assert_eq!(values(&matmul(&rows, &w)), values(&matmul(&copy, &w)));
+        assert_eq!(values(&matmul(&w.t(), &rows.t())), values(&matmul(&w.t(), &copy.t())));
+
+        // Attention against a window of a KV cache [heads, capacity, dim]
+        let cache = Tensor::from_vec_f32(data(2 * 6 * 8), vec![2, 6, 8]);
+        let keys = cache.narrow(1, 1, 4);
+        let q = Tensor::from_vec_f32(data(2 * 3 * 8), vec![2, 3, 8]).narrow(1, 1, 2);
+        let expected = matmul(&Tensor::from_vec_f32(values(&q), vec![2, 2, 8]), &Tensor::from_vec_f32(values(&keys), vec![2, 4, 8]).t());
+        assert_eq!(values(&matmul(&q, &keys.t())), values(&expected));
+
+        // INT4: sliced activations, weight rows, packed columns and scales
+        let (n, k) = (3, 4);
+        let packed = Tensor::zeros(vec![5, 4], DType::I8);
+        for r in 0..5 {
+            for c in 0..4 {
+                unsafe { packed.set::<i8>(&[r, c], ((r * 4 + c) * 53 % 256) as u8 as i8).unwrap() };
+            }
+        }
+        let weight = packed.narrow(0, 1, n).narrow(1, 1, k / 2);
+        let weight_copy = Tensor::zeros(vec![n, k / 2], DType::I8);
+        unsafe { weight_copy.copy_from(&weight) };
+        let scales = Tensor::from_vec_f32(vec![0.5, 1.0, 1.5, 2.0, 2.5], vec![5]).narrow(0, 2, n);
+        let scales_copy = Tensor::from_vec_f32(values(&scales), vec![n]);
+        let x = Tensor::from_vec_f32(data(6 * k), vec![k, 6]).t().narrow(0, 2, 2);
+        let x_copy = Tensor::from_vec_f32(values(&x), vec![2, k]);
+        assert_eq!(
+            values(&matmul_int4(&x, &weight, &scales, &None)),
+            values(&matmul_int4(&x_copy, &weight_copy, &scales_copy, &None))
+        );
+    }
+
+    #[test]
+    fn test_add_broadcast() {
+        // [2, 3] + [3] -> row bias
+        let a = Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2, 3]);
+        let b = Tensor::from_vec_f32(vec![10.0, 20.0, 30.0], vec![3]);
+        let c = add(&a, &b);
+        assert_eq!(c.shape(), &[2, 3]);
+        assert_eq!(values(&c), vec![11.0, 22.0, 33.0, 14.0, 25.0, 36.0]);
+
+        // [2, 1] + [1, 3] -> outer sum, both sides stretch
+        let col = Tensor::from_vec_f32(vec![1.0, 2.0], vec![2, 1]);
+        let row = Tensor::from_vec_f32(vec![10.0, 20.0, 30.0], vec![1, 3]);
+        let c = add(&col, &row);
+        assert_eq!(c.shape(), &[2, 3]);
+        assert_eq!(values(&c), vec![11.0, 21.0, 31.0, 12.0, 22.0, 32.0]);
+
+        // Transposed view operand
+        let at = a.t(); // [3, 2]
+        let c = add(&at, &Tensor::from_vec_f32(vec![0.0, 100.0], vec![2]));
+        assert_eq!(values(&c), vec![1.0, 104.0, 2.0, 105.0, 3.0, 106.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "not broadcastable")]
+    fn test_add_broadcast_incompatible() {
+        let a = Tensor::zeros(vec![2, 3], DType::F32);
+        let b = Tensor::zeros(vec![2], DType::F32);
+        let _ = add(&a, &b);
+    }
+
+    #[test]
+    fn test_add_backward_reduces_broadcast() {
+        let mut a = Tensor::zeros(vec![2, 3], DType::F32);
+        a.requires_grad = true;
+        let mut b = Tensor::zeros(vec![3], DType::F32);
+        b.requires_grad = true;
+
+        let c = add(&a, &b);
+        let grad = Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2, 3]);
+        let grads = c.ctx.as_ref().unwrap().backward(&grad);
+
+        assert_eq!(grads[0].shape(), &[2, 3]);
+        assert_eq!(grads[1].shape(), &[3]);
+        assert_eq!(values(&grads[1]), vec![5.0, 7.0, 9.0]);
+    }
+
+    #[test]
+    fn test_linear_batched_bias() {
+        let mut layer = Linear::new(2, 3, true);
+        layer.bias = Some(Tensor::from_vec_f32(vec![0.5, 1.0, 1.5], vec![3]));
+        let x = Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]);
+        let y = layer.forward(&x);
+        assert_eq!(y.shape(), &[2, 3]);
+        assert_eq!(values(&y), vec![3.5, 4.0, 4.5, 7.5, 8.0, 8.5]);
+    }
+
+    #[test]
+    fn test_elementwise_family() {
+        let a = Tensor::from_vec_f32(vec![1.0, 4.0, 9.0, 2.0], vec![2, 2]);
+        let b = Tensor::from_vec_f32(vec![2.0, 2.0], vec![2]);
+
+        assert_eq!(values(&sub(&a, &b)), vec![-1.0, 2.0, 7.0, 0.0]);
+        assert_eq!(values(&mul(&a, &b)), vec![2.0, 8.0, 18.0, 4.0]);
+        assert_eq!(values(&div(&a, &b)), vec![0.5, 2.0, 4.5, 1.0]);
+        assert_eq!(values(&pow(&a, &b)), vec![1.0, 16.0, 81.0, 4.0]);
+        assert_eq!(values(&maximum(&a, &b)), vec![2.0, 4.0, 9.0, 2.0]);
+        assert_eq!(values(&minimum(&a, &b)), vec![1.0, 2.0, 2.0, 2.0]);
+
+        assert_eq!(values(&mul_scalar(&a, 0.5)), vec![0.5, 2.0, 4.5, 1.0]);
+        assert_eq!(values(&sub_scalar(&a, 1.0)), vec![0.0, 3.0, 8.0, 1.0]);
+        assert_eq!(values(&pow_scalar(&a, 0.5)), vec![1.0, 2.0, 3.0, std::f32::consts::SQRT_2]);
+
+        // Strided operand: a.t() reads [[1, 9], [4, 2]]
+        assert_eq!(values(&mul(&a.t(), &b)), vec![2.0, 18.0, 8.0, 4.0]);
+    }
+
+    #[test]
+    fn test_elementwise_backward() {
+        let mut x = Tensor::from_vec_f32(vec![1.0, 3.0], vec![2]);
+        x.requires_grad = true;
+        let mut y = Tensor::from_vec_f32(vec![2.0, 3.0], vec![2]);
+        y.requires_grad = true;
+        let ones = Tensor::ones(vec![2], DType::F32);
+
+        // d(x*y) = (y, x)
+        let g = mul(&x, &y).ctx.as_ref().unwrap().backward(&ones);
+        assert_eq!(values(&g[0]), vec![2.0, 3.0]);
+        assert_eq!(values(&g[1]), vec![1.0, 3.0]);
+
+        // d(x/y) = (1/y, -x/y^2)
+        let g = div(&x, &y).ctx.as_ref().unwrap().backward(&ones);
+        assert_eq!(values(&g[0]), vec![0.5, 1.0 / 3.0]);
+        assert_eq!(values(&g[1]), vec![-0.25, -1.0 / 3.0]);
+
+        // d(x^y) = (y*x^(y-1), x^y*ln x)
+        let g = pow(&x, &y).ctx.as_ref().unwrap().backward(&ones);
+        assert_eq!(values(&g[0]), vec![2.0, 27.0]);
+        assert!((values(&g[1])[1] - 27.0 * 3.0f32.ln()).abs() < 1e-4);
+
+        // maximum: gradient goes to the winner, ties split
+        let g = maximum(&x, &y).ctx.as_ref().unwrap().backward(&ones);
+        assert_eq!(values(&g[0]), vec![0.0, 0.5]);
+        assert_eq!(values(&g[1]), vec![1.0, 0.5]);
+
+        // sub with broadcast rhs reduces and negates
+        let mut s = Tensor::from_vec_f32(vec![1.0], vec![1]);
+        s.requires_grad = true;
+        let g = sub(&x, &s).ctx.as_ref().unwrap().backward(&ones);
+        assert_eq!(values(&g[1]), vec![-2.0]);
+    }
+
+    #[test]
+    fn test_strided_walker() {
+        // Offsets of a [2, 3] tensor read through its transpose strides
+        let mut seen = Vec::new();
+        for_each_offset(&[3, 2], [&[1, 3]], |i, [off]| seen.push((i, off)));
+        assert_eq!(seen, vec![(0, 0), (1, 3), (2, 1), (3, 4), (4, 2), (5, 5)]);
+
+        // Contiguous fast path yields identical offsets
+        let mut seen = Vec::new();
+        for_each_offset(&[2, 1, 2], [&[2, 7, 1]], |_, [off]| seen.push(off));
+        assert_eq!(seen, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_relu_transposed_view() {
+        let mut a = Tensor::from_vec_f32(vec![-1.0, 2.0, 3.0, -4.0, 5.0, -6.0], vec![2, 3]);
+        a.requires_grad = true;
+        let at = a.t(); // [[-1, -4], [2, 5], [3, -6]]
+        let y = relu(&at);
+        assert_eq!(values(&y), vec![0.0, 0.0, 2.0, 5.0, 3.0, 0.0]);
+
+        // Gradient arriving as a transposed view must be masked elementwise, not by flat index
+        let grad = Tensor::from_vec_f32(vec![10.0, 20.0, 30.0, 40.0, 50.0, 60.0], vec![2, 3]).t();
+        let g = y.ctx.as_ref().unwrap().backward(&grad);
+        assert_eq!(values(&g[0]), vec![0.0, 0.0, 20.0, 50.0, 30.0, 0.0]);
+    }
+
+    #[test]
+    fn test_dense_kernels_accept_views() {
+        // Zero nibbles dequantize to -8 * scale
+        let w = Tensor::zeros(vec![2, 1], DType::I8);
+        let scales = Tensor::ones(vec![2], DType::F32);
+        let x = Tensor::from_vec_f32(vec![1.0, 3.0, 2.0, 4.0], vec![2, 2]).t(); // [[1, 2], [3, 4]]
+        let y = matmul_int4(&x, &w, &scales, &None);
+        assert_eq!(values(&y), vec![-24.0, -24.0, -56.0, -56.0]);
+
+        // RoPE with cos = 1, sin = 0 is the identity, also for a transposed input
+        let x = Tensor::from_vec_f32(vec![1.0, 3.0, 2.0, 4.0], vec![2, 2]).t();
+        let cos = Tensor::ones(vec![2], DType::F32);
+        let sin = Tensor::zeros(vec![2], DType::F32);
+        assert_eq!(values(&rope(&x, &cos, &sin)), vec![1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_fallible_ops() {
+        let a = Tensor::zeros(vec![2, 3], DType::F32);
+        let b = Tensor::zeros(vec![2], DType::F32);
+        assert!(matches!(try_add(&a, &b), Err(TensorError::IncompatibleShapes { .. })));
+        assert!(matches!(try_add(&a, &Tensor::zeros(vec![3], DType::F16)), Err(TensorError::DTypeMismatch { .. })));
+
+        assert!(matches!(try_matmul(&a, &a), Err(TensorError::ShapeMismatch { .. })));
+        assert!(matches!(try_matmul(&b, &b), Err(TensorError::Unsupported(_))));
+        assert!(try_matmul(&a, &a.t()).is_ok());
+
+        let w = Tensor::zeros(vec![4, 2], DType::I8);
+        let scales = Tensor::ones(vec![4], DType::F32);
+        assert!(matches!(try_matmul_int4(&a, &w, &scales, &None), Err(TensorError::Unsupported(_))));
+        let x = Tensor::zeros(vec![2, 4], DType::F32);
+        assert!(try_matmul_int4(&x, &w, &scales, &None).is_ok());
+        assert!(matches!(try_matmul_int4(&x, &w, &b, &None), Err(TensorError::ShapeMismatch { .. })));
+
+        // Scalar ops compute in F32, so integer tensors are rejected rather than zeroed
+        type ScalarOp = fn(&Tensor, f32) -> crate::error::Result<Tensor>;
+        let scalar_ops: [ScalarOp; 5] = [try_add_scalar, try_sub_scalar, try_mul_scalar, try_div_scalar, try_pow_scalar];
+        for op in scalar_ops {
+            assert!(matches!(op(&w, 2.0), Err(TensorError::Unsupported(_))));
+            assert!(matches!(op(&Tensor::zeros(vec![2], DType::I64), 2.0), Err(TensorError::Unsupported(_))));
+            assert!(op(&Tensor::zeros(vec![2], DType::F16), 2.0).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_reductions() {
+        // [[1, 5, 3], [4, 2, 6]]
+        let x = Tensor::from_vec_f32(vec![1.0, 5.0, 3.0, 4.0, 2.0, 6.0], vec![2, 3]);
+
+        let s = sum(&x, None, false);
+        assert_eq!(s.shape(), &[] as &[usize]);
+        assert_eq!(values(&s), vec![21.0]);
+        assert_eq!(sum(&x, None, true).shape(), &[1, 1]);
+        assert_eq!(values(&sum(&x, Some(0), false)), vec![5.0, 7.0, 9.0]);
+        let s1 = sum(&x, Some(1), true);
+        assert_eq!(s1.shape(), &[2, 1]);
+        assert_eq!(values(&s1), vec![9.0, 12.0]);
+
+        assert_eq!(values(&mean(&x, Some(1), false)), vec![3.0, 4.0]);
+        assert_eq!(values(&max(&x, Some(0), false)), vec![4.0, 5.0, 6.0]);
+        assert_eq!(values(&min(&x, None, false)), vec![1.0]);
+        assert_eq!(values(&prod(&x, Some(1), false)), vec![15.0, 48.0]);
+
+        let am = argmax(&x, Some(1), false);
+        assert_eq!(am.dtype(), DType::I64);
+        assert_eq!(am.to_vec::<i64>().unwrap(), vec![1, 2]);
+        assert_eq!(argmin(&x, Some(0), true).to_vec::<i64>().unwrap(), vec![0, 1, 0]);
+        // Flat index for full reductions; ties go to the first occurrence
+        assert_eq!(argmax(&x, None, false).item::<i64>().unwrap(), 5);
+        let tied = Tensor::from_vec_f32(vec![2.0, 7.0, 7.0], vec![3]);
+        assert_eq!(argmax(&tied, None, false).item::<i64>().unwrap(), 1);
+
+        // Strided views reduce along their logical dims
+        let xt = x.t(); // [[1, 4], [5, 2], [3, 6]]
+        assert_eq!(values(&sum(&xt, Some(1), false)), vec![5.0, 7.0, 9.0]);
+        assert_eq!(values(&max(&xt, None, false)), vec![6.0]);
+        assert_eq!(argmin(&xt, Some(0), false).to_vec::<i64>().unwrap(), vec![0, 1]);
+
+        assert!(matches!(try_sum(&x, Some(2), false), Err(TensorError::OutOfBounds { .. })));
+        let empty = Tensor::zeros(vec![2, 0], DType::F32);
+        assert_eq!(values(&sum(&empty, Some(1), false)), vec![0.0, 0.0]);
+        assert!(matches!(try_max(&empty, Some(1), false), Err(TensorError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_reductions_propagate_nan() {
+        let x = Tensor::from_vec_f32(vec![1.0, f32::NAN, 3.0, f32::NAN, 2.0, 0.5], vec![2, 3]);
+        assert!(values(&max(&x, None, false))[0].is_nan());
+        assert!(values(&min(&x, None, false))[0].is_nan());
+        // Per row, max/min and argmax/argmin agree on the NaN
+        assert!(values(&max(&x, Some(1), false)).iter().all(|v| v.is_nan()));
+        assert_eq!(argmax(&x, Some(1), false).to_vec::<i64>().unwrap(), vec![1, 0]);
+        assert_eq!(argmin(&x, Some(1), false).to_vec::<i64>().unwrap(), vec![1, 0]);
+        assert_eq!(argmax(&x, None, false).item::<i64>().unwrap(), 1);
+        let cols = max(&x, Some(0), false);
+        let v = values(&cols);
+        assert!(v[0].is_nan() && v[1].is_nan());
+        assert_eq!(v[2], 3.0);
+        assert_eq!(argmin(&x, Some(0), false).to_vec::<i64>().unwrap(), vec![1, 0, 1]);
+
+        // Gradient of a NaN result flows to the NaN element
+        let mut leaf = Tensor::from_vec_f32(vec![1.0, f32::NAN, 3.0], vec![3]);
+        leaf.requires_grad = true;
+        let g = max(&leaf, None, false).ctx.as_ref().unwrap().backward(&Tensor::ones(vec![], DType::F32));
+        assert_eq!(values(&g[0]), vec![0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_parallel_reductions() {
+        // Large enough to take the rayon path, with lanes split into several chunks
+        let n = 3 * 40_000;
+        let data: Vec<f32> = (0..n).map(|i| ((i * 7919) % 1000) as f32 / 100.0).collect();
+        let x = Tensor::from_vec_f32(data.clone(), vec![3, 40_000]);
+
+        let rows = sum(&x, Some(1), false).to_vec_f32().unwrap();
+        for (r, &got) in rows.iter().enumerate() {
+            let expected: f64 = data[r * 40_000..(r + 1) * 40_000].iter().map(|&v| v as f64).sum();
+            assert!((got as f64 - expected).abs() < 1e-3 * expected, "row {}: {} vs {}", r, got, expected);
+        }
+
+        let mut peaked = data.clone();
+        peaked[77_777] = 50.0;
+        peaked[99_999] = 50.0;
+        let y = Tensor::from_vec_f32(peaked, vec![n]);
+        assert_eq!(argmax(&y, None, false).item::<i64>().unwrap(), 77_777);
+        assert_eq!(values(&max(&y.reshape(&[3, 40_000]).t(), None, false)), vec![50.0]);
+        assert_eq!(argmax(&y.reshape(&[3, 40_000]), Some(1), false).to_vec::<i64>().unwrap()[1], 37_777);
+    }
+
+    #[test]
+    fn test_thread_pool() {
+        use crate::parallel::{num_threads, set_num_threads, with_thread_pool};
+        use std::sync::Arc;
+
+        let data = |n: usize| (0..n).map(|i| ((i * 7919) % 1000) as f32 / 500.0 - 1.0).collect::<Vec<f32>>();
+        let weight = Tensor::from_vec_f32(data(700 * 900), vec![900, 700]);
+        let token = Tensor::from_vec_f32(data(700), vec![1, 700]);
+        let x = Tensor::from_vec_f32(data(130 * 300), vec![300, 130]);
+        let run = || {
+            vec![
+                // Decode-shaped GEMM (one row, split across column groups) on a transposed weight
+                values(&matmul(&token, &weight.t())),
+                values(&matmul(&x.t(), &weight.narrow(0, 0, 300).narrow(1, 0, 200).contiguous())),
+                // Strided elementwise, SIMD pointwise, broadcast binary, reductions, softmax
+                values(&sub_scalar(&weight.t(), 0.5)),
+                values(&sigmoid(&weight)),
+                values(&add(&weight, &token.reshape(&[700]).narrow(0, 0, 700))),
+                values(&sum(&weight.t(), Some(1), false)),
+                // Lanes longer than one chunk
+                values(&sum(&weight, None, false)),
+                values(&softmax(&weight, 1)),
+            ]
+        };
+
+        let single = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap());
+        let quad = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap());
+        let serial = {
+            let _guard = with_thread_pool(single);
+            assert_eq!(num_threads(), 1);
+            run()
+        };
+        let parallel = {
+            let _guard = with_thread_pool(quad);
+            assert_eq!(num_threads(), 4);
+            run()
+        };
+        // Work is split without changing any summation order
+        assert_eq!(serial, parallel);
+
+        set_num_threads(3).unwrap();
+        assert_eq!(num_threads(), 3);
+        set_num_threads(0).unwrap();
+        assert_eq!(num_threads(), rayon::current_num_threads());
+    }
+
+    fn assert_close(a: &[f32], b: &[f32], tol: f32) {
+        assert_eq!(a.len(), b.len());
+        for (i, (x, y)) in a.iter().zip(b).enumerate() {
+            assert!((x - y).abs() <= tol * (1.0 + y.abs()), "element {}: {} vs {}", i, x, y);
+        }
+    }
+
+    #[test]
+    fn test_softmax() {
+        let x = Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 1000.0, 1000.0, f32::NEG_INFINITY], vec![2, 3]);
+        let e = [1.0f32.exp(), 2.0f32.exp(), 3.0f32.exp()];
+        let z: f32 = e.iter().sum();
+
+        // Max-subtraction keeps large logits finite; -inf (masked) entries get exactly 0
+        let y = softmax(&x, 1);
+        assert_close(&values(&y), &[e[0] / z, e[1] / z, e[2] / z, 0.5, 0.5, 0.0], 1e-6);
+        let ly = log_softmax(&x, 1);
+        assert_close(&values(&ly)[..5], &[1.0 - z.ln(), 2.0 - z.ln(), 3.0 - z.ln(), -std::f32::consts::LN_2, -std::f32::consts::LN_2], 1e-6);
+        assert_eq!(values(&ly)[5], f32::NEG_INFINITY);
+
+        // Along dim 0 (strided lanes)
+        let cols = values(&softmax(&x, 0));
+        assert_close(&cols[..3], &[0.0, 0.0, 1.0], 1e-6);
+        assert!(matches!(try_softmax(&x, 2), Err(TensorError::OutOfBounds { .. })));
+    }
+
+    #[test]
+    fn test_softmax_row_kernel_matches_strided() {
+        // Rows of 37 cover both the 8-wide body and the scalar tail of the SIMD kernel,
+        // and enough rows to take the parallel path
+        let (rows, cols) = (1024, 37);
+        let data: Vec<f32> = (0..rows * cols).map(|i| ((i * 7919) % 2000) as f32 / 100.0 - 10.0).collect();
+        let x = Tensor::from_vec_f32(data, vec![rows, cols]);
+
+        let fast = softmax(&x, 1);
+        let strided = softmax(&x.t(), 0).t();
+        assert_close(&values(&fast), &values(&strided), 1e-6);
+        assert_close(&values(&log_softmax(&x, 1)), &values(&log_softmax(&x.t(), 0).t()), 1e-5);
+
+        let row_sums = values(&sum(&fast, Some(1), false));
+        assert_close(&row_sums, &vec![1.0; rows], 1e-5);
+    }
+
+    #[test]
+    fn test_activations() {
+        // 46 values: covers the SIMD body and tail, large magnitudes, zero and tiny inputs
+        let mut xs: Vec<f32> = (0..37).map(|i| (i as f32 - 18.0) * 0.75).collect();
+        xs.extend([2e-6, -2e-6, 2e-3, -2e-3, 0.1, -0.3, 0.62, -0.63, 0.7]);
+        let x = Tensor::from_vec_f32(xs.clone(), vec![xs.len()]);
+
+        fn erf64(x: f64) -> f64 {
+            // Independent of the A&S approximation under test: Simpson's rule on exp(-t^2)
+            let n = 2000;
+            let h = x / n as f64;
+            let f = |t: f64| (-t * t).exp();
+            let mut acc = f(0.0) + f(x);
+            for k in 1..n {
+                acc += f(k as f64 * h) * if k % 2 == 1 { 4.0 } else { 2.0 };
+            }
+            acc * h / 3.0 * 2.0 / std::f64::consts::PI.sqrt()
+        }
+        let sig = |x: f64| 1.0 / (1.0 + (-x).exp());
+        type Reference = Box<dyn Fn(f64) -> f64>;
+        // Relative tolerance in ulps, down to subnormal results (which may flush to zero).
+        // GELU adds the absolute error of the A&S `erf` (1.5e-7); in the negative tail of the
+        // tanh form, exp(2u) amplifies the rounding of the cubic u.
+        let cases: Vec<(Tensor, Reference, f32, f32)> = vec![
+            (sigmoid(&x), Box::new(sig), 4.0, 0.0),
+            (tanh(&x), Box::new(f64::tanh), 4.0, 0.0),
+            (silu(&x), Box::new(move |x| x * sig(x)), 4.0, 0.0),
+            (gelu(&x), Box::new(move |x| 0.5 * x * (1.0 + erf64(x / std::f64::consts::SQRT_2))), 4.0, 3e-7),
+            // 0.5 x (1 + tanh(u)) == x sigmoid(2u), which keeps the negative tail from cancelling to 0
+            (gelu_tanh(&x), Box::new(move |x| x * sig(2.0 * (2.0 / std::f64::consts::PI).sqrt() * (x + 0.044715 * x * x * x))), 64.0, 0.0),
+            (softplus(&x, 1.0), Box::new(|x| x.exp().ln_1p()), 4.0, 0.0),
+            (leaky_relu(&x, 0.1), Box::new(|x| if x > 0.0 { x } else { 0.1 * x }), 1.0, 0.0),
+        ];
+        for (k, (out, reference, ulps, abs)) in cases.iter().enumerate() {
+            let expected: Vec<f32> = xs.iter().map(|&v| reference(v as f64) as f32).collect();
+            let got = values(out);
+            for (i, (g, e)) in got.iter().zip(&expected).enumerate() {
+                let tol = ulps * f32::EPSILON * e.abs() + abs + f32::MIN_POSITIVE;
+                assert!((g - e).abs() <= tol, "case {} x = {}: {} vs {}", k, xs[i], g, e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_math_ops() {
+        // 37 values: covers the SIMD body and tail; positive for log/sqrt/rsqrt
+        let xs: Vec<f32> = (0..37).map(|i| (i as f32 - 18.0) * 0.9).collect();
+        let pos: Vec<f32> = (0..37).map(|i| 1e-3 + i as f32 * 2.5).collect();
+        let x = Tensor::from_vec_f32(xs.clone(), vec![37]);
+        let p = Tensor::from_vec_f32(pos.clone(), vec![37]);
+
+        type Op = fn(&Tensor) -> Tensor;
+        type Reference = fn(f32) -> f32;
+        let cases: Vec<(Op, Reference, &Tensor, &[f32])> = vec![
+            (exp, f32::exp, &x, &xs),
+            (log, f32::ln, &p, &pos),
+            (sqrt, f32::sqrt, &p, &pos),
+            (rsqrt, |v| 1.0 / v.sqrt(), &p, &pos),
+            (abs, f32::abs, &x, &xs),
+            (neg, |v| -v, &x, &xs),
+            (sin, f32::sin, &x, &xs),
+            (cos, f32::cos, &x, &xs),
+            (reciprocal, f32::recip, &x, &xs),
+            (|t| clamp(t, -2.0, 3.5), |v| v.clamp(-2.0, 3.5), &x, &xs),
+        ];
+        for (k, (op, reference, input, inputs)) in cases.iter().enumerate() {
+            let expected: Vec<f32> = inputs.iter().map(|&v| reference(v)).collect();
+            // Fast kernels stay within a few ulp of std
+            for (i, (g, e)) in values(&op(input)).iter().zip(&expected).enumerate() {
+                assert!(g == e || (g - e).abs() <= 4.0 * f32::EPSILON * e.abs().max(1e-6), "case {} element {}: {} vs {}", k, i, g, e);
+            }
+            // Exact mode matches std bit for bit
+            let _exact = with_precision(Precision::Exact);
+            assert_eq!(values(&op(input)), expected, "case {}", k);
+        }
+        assert_eq!(precision(), Precision::Fast);
+
+        // Special values agree with std in both modes
+        let special = vec![0.0, -0.0, f32::INFINITY, f32::NEG_INFINITY, f32::NAN, -1.0, 100.0, -100.0, 1e-30];
+        let t = Tensor::from_vec_f32(special.clone(), vec![9]);
+        for mode in [Precision::Fast, Precision::Exact] {
+            let _guard = with_precision(mode);
+            for (op, reference) in [(exp as Op, f32::exp as Reference), (log, f32::ln), (rsqrt, |v| 1.0 / v.sqrt()), (sin, f32::sin), (cos, f32::cos)] {
+                for (&v, got) in special.iter().zip(values(&op(&t))) {
+                    let want = reference(v);
+                    let ok = (want.is_nan() && got.is_nan()) || (want.is_infinite() && got == want) || (got - want).abs() <= 4.0 * f32::EPSILON * want.abs().max(1e-30);
+                    assert!(ok, "{:?} at {}: {} vs {}", mode, v, got, want);
+                }
+            }
+        }
+        assert!(values(&clamp(&t, -1.0, 1.0))[4].is_nan());
+
+        // exp near overflow (2^128 is scaled in two steps) and through the subnormal range
+        let edges: Vec<f32> = vec![
+            88.3, 88.376_26, 88.38, 88.45, 88.5, 88.6, 88.7, 88.72, 88.722_8, 88.722_84, 88.73, 89.0,
+            -87.0, -87.4, -88.0, -88.376_26, -88.5, -89.0, -95.0, -100.0, -103.2, -103.9, -104.0, -110.0,
+        ];
+        for (&v, got) in edges.iter().zip(values(&exp(&Tensor::from_vec_f32(edges.clone(), vec![24])))) {
+            let want = v.exp();
+            let ok = got == want || (got - want).abs() <= 4.0 * f32::EPSILON * want.max(f32::MIN_POSITIVE);
+            assert!(ok, "exp({}): {} vs {}", v, got, want);
+        }
+
+        // Strided and F16 inputs take the scalar path
+        let m = Tensor::from_vec_f32(xs[..12].to_vec(), vec![3, 4]);
+        assert_eq!(values(&exp(&m.t())), values(&m.t().contiguous()).iter().map(|v| v.exp()).collect::<Vec<_>>());
+        let h = Tensor::ones(vec![2], DType::F16);
+        assert_eq!(neg(&h).to_vec::<half::f16>().unwrap()[0].to_f32(), -1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "must not exceed")]
+    fn test_clamp_rejects_inverted_bounds() {
+        clamp(&Tensor::zeros(vec![2], DType::F32), 1.0, 0.0);
+    }
+
+    #[test]
+    fn test_unary_rejects_integer_dtypes() {
+        use crate::ops::unary::*;
+        use crate::ops::strided::{try_map_unary, try_map_binary};
+
+        type UnaryOp = fn(&Tensor) -> crate::error::Result<Tensor>;
+        let ops: [UnaryOp; 18] = [
+            try_relu, |x| try_leaky_relu(x, 0.1), try_sigmoid, try_tanh, try_silu, try_gelu, try_gelu_tanh,
+            |x| try_softplus(x, 1.0), try_exp, try_log, try_sqrt, try_rsqrt, try_abs, try_neg, try_sin, try_cos,
+            |x| try_clamp(x, 0.0, 1.0), try_reciprocal,
+        ];
+        for dtype in [DType::I8, DType::I64] {
+            let t = Tensor::zeros(vec![3], dtype);
+            for op in ops {
+                assert!(matches!(op(&t), Err(TensorError::Unsupported(_))));
+            }
+        }
+        let f16 = Tensor::ones(vec![3], DType::F16);
+        assert!(ops.iter().all(|op| op(&f16).is_ok()));
+        assert!(matches!(try_clamp(&f16, f32::NAN, 1.0), Err(TensorError::InvalidArgument(_))));
+
+        // The strided helpers report errors instead of returning zeros
+        let i = Tensor::zeros(vec![3], DType::I64);
+        assert!(matches!(try_map_unary(&i, |x| x), Err(TensorError::Unsupported(_))));
+        assert!(matches!(try_map_binary(&i, &i, |a, _| a), Err(TensorError::Unsupported(_))));
+        assert!(matches!(try_map_binary(&f16, &i, |a, _| a), Err(TensorError::DTypeMismatch { .. })));
+        let two = Tensor::zeros(vec![2], DType::F16);
+        assert!(matches!(try_map_binary(&f16, &two, |a, _| a), Err(TensorError::IncompatibleShapes { .. })));
+    }
+
+    #[test]
+    fn test_activations_strided_and_f16() {
+        let data: Vec<f32> = (0..24).map(|i| (i as f32 - 12.0) * 0.3).collect();
+        let x = Tensor::from_vec_f32(data, vec![4, 6]);
+        // Dense input takes the SIMD kernel, the transposed view the scalar reference
+        let activations: [fn(&Tensor) -> Tensor; 9] = [
+            sigmoid,
+            tanh,
+            silu,
+            gelu,
+            gelu_tanh,
+            |x| softplus(x, 1.0),
+            // Crosses the `beta * x > 20` cutoff inside the range
+            |x| softplus(x, 10.0),
+            |x| leaky_relu(x, 0.1),
+            |x| leaky_relu(x, -2.0),
+        ];
+        for f in activations {
+            let dense = values(&f(&x).t());
+            let strided = values(&f(&x.t()));
+            for (a, b) in dense.iter().zip(&strided) {
+                assert!((a - b).abs() <= 1e-6 * (1.0 + b.abs()), "{} vs {}", a, b);
+            }
+        }
+
+        // Softplus deep in the negative tail, where ln(1 + e) must not round to 0 or lose digits
+        let tail = Tensor::from_vec_f32((0..16).map(|i| -10.0 - i as f32 * 6.0).collect(), vec![16]);
+        for (a, b) in values(&softplus(&tail, 1.0)).iter().zip(&values(&softplus(&tail.view(&[4, 4]).t(), 1.0).t())) {
+            assert!((a - b).abs() <= 4.0 * f32::EPSILON * b.abs() + f32::MIN_POSITIVE, "{} vs {}", a, b);
+        }
+
+        // F16 is computed in F32 and rounded once
+        let h = Tensor::zeros(vec![2], DType::F16);
         unsafe {
-            let ptr = c.storage.as_ptr() as *const f32;
-            assert_eq!(*ptr, 1.0);
-            assert_eq!(*ptr.add(1), 2.0);
-            assert_eq!(*ptr.add(2), 3.0);
-            assert_eq!(*ptr.add(3), 4.0);
+            h.set(&[0], half::f16::from_f32(1.0)).unwrap();
+            h.set(&[1], half::f16::from_f32(-2.0)).unwrap();
         }
+        let y = silu(&h);
+        assert_eq!(y.dtype(), DType::F16);
+        let y = y.to_vec::<half::f16>().unwrap();
+        assert_eq!(y[0], half::f16::from_f32(1.0 / (1.0 + (-1.0f32).exp())));
+        assert_eq!(y[1], half::f16::from_f32(-2.0 / (1.0 + 2.0f32.exp())));
     }
 }
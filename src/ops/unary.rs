@@ -1,10 +1,151 @@
+use std::sync::Arc;
+use std::f32::consts::{FRAC_1_SQRT_2, FRAC_2_SQRT_PI};
 use crate::tensor::{Tensor, DType};
 use crate::autograd::node::Node;
+use crate::autograd::grad_mode::needs_grad;
+use crate::ops::binary::{add, add_scalar, div, mul, mul_scalar};
+use crate::ops::simd::{self, Kernel};
+use crate::error::{Result, TensorError};
+use crate::ops::strided::{map_binary, map_unary, try_map_unary};
+
+// Pointwise activations and math. Any layout is accepted; F16 is computed in F32.
+// Dense F32 inputs use the SIMD kernel from `ops::simd` when the CPU has one; approximate
+// kernels fall back to `std` math under `Precision::Exact` (see `ops::precision`).
+
+/// Applies `f` elementwise, through the SIMD `kernel` when the input is dense F32.
+fn pointwise(input: &Tensor, f: impl Fn(f32) -> f32 + Sync, kernel: Option<Kernel>) -> Result<Tensor> {
+    if let (Some(kernel), DType::F32, true) = (kernel, input.dtype, input.is_contiguous()) {
+        let output = Tensor::zeros(input.shape.clone(), DType::F32);
+        let numel = input.numel();
+        let done = unsafe {
+            let src = std::slice::from_raw_parts(input.storage.as_ptr().add(input.offset) as *const f32, numel);
+            let dst = std::slice::from_raw_parts_mut(output.storage.as_ptr() as *mut f32, numel);
+            simd::map_dense(kernel, src, dst)
+        };
+        if done {
+            return Ok(output);
+        }
+    }
+    try_map_unary(input, f)
+}
+
+/// Attaches the Node built by `node` when `input` requires grad.
+fn record(output: Tensor, input: &Tensor, node: impl FnOnce() -> Arc<dyn Node>) -> Tensor {
+    if needs_grad(&[input]) {
+        let mut out = output;
+        out.requires_grad = true;
+        out.ctx = Some(node());
+        return out;
+    }
+    output
+}
+
+/// Whether a backward pass must be built from differentiable ops.
+fn recording(grad: &Tensor, input: &Tensor) -> bool {
+    needs_grad(&[grad, input])
+}
+
+/// Chain rule for a piecewise-linear op: `grad * df(x)` with `df` piecewise constant.
+/// When recording (create_graph) the mask is a recorded [`StepNode`], so the gradient stays
+/// attached to `x` and differentiates to zero.
+fn chain(grad: &Tensor, x: &Tensor, df: impl Fn(f32) -> f32 + Sync) -> Tensor {
+    if recording(grad, x) {
+        let mask = record(map_unary(x, &df), x, || Arc::new(StepNode { input: x.clone() }));
+        return mul(grad, &mask);
+    }
+    map_binary(grad, x, |g, x| g * df(x))
+}
+
+/// Chain rule for a smooth pointwise op: `grad * df(x)`. When recording (create_graph)
+/// the derivative comes from `recorded_df`, built from differentiable ops so that higher
+/// derivatives are tracked too.
+fn chain_smooth(
+    grad: &Tensor,
+    x: &Tensor,
+    df: impl Fn(f32) -> f32 + Sync,
+    recorded_df: impl FnOnce(&Tensor) -> Tensor,
+) -> Tensor {
+    if recording(grad, x) {
+        return mul(grad, &recorded_df(x));
+    }
+    map_binary(grad, x, |g, x| g * df(x))
+}
+
+/// `1 - t`, recorded.
+fn one_minus(t: &Tensor) -> Tensor {
+    add_scalar(&neg(t), 1.0)
+}
+
+/// Derivative mask of a piecewise-linear op. Its own derivative is zero wherever it is defined.
+#[derive(Debug)]
+pub struct StepNode {
+    input: Tensor,
+}
+
+impl Node for StepNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![Tensor::zeros(self.input.shape.clone(), grad.dtype)]
+    }
+}
+
+/// Error function, Abramowitz & Stegun 7.1.26 (absolute error < 1.5e-7).
+pub(crate) fn erf(x: f32) -> f32 {
+    let a = x.abs();
+    let t = 1.0 / (1.0 + 0.327_591_1 * a);
+    let p = ((((1.061_405_4 * t - 1.453_152_1) * t + 1.421_413_8) * t - 0.284_496_72) * t + 0.254_829_6) * t;
+    (1.0 - p * (-a * a).exp()).copysign(x)
+}
+
+fn sigmoid_f32(x: f32) -> f32 {
+    // Never exponentiates a positive number, so neither side overflows
+    if x >= 0.0 {
+        1.0 / (1.0 + (-x).exp())
+    } else {
+        let e = x.exp();
+        e / (1.0 + e)
+    }
+}
+
+const GELU_TANH_C: f32 = 0.797_884_6; // sqrt(2 / pi)
+const GELU_TANH_K: f32 = 0.044_715;
+
+fn gelu_f32(x: f32) -> f32 {
+    0.5 * x * (1.0 + erf(x * FRAC_1_SQRT_2))
+}
+
+/// 1 / sqrt(2 pi)
+const INV_SQRT_2PI: f32 = FRAC_2_SQRT_PI * FRAC_1_SQRT_2 * 0.5;
+
+fn normal_cdf_f32(x: f32) -> f32 {
+    0.5 * (1.0 + erf(x * FRAC_1_SQRT_2))
+}
+
+fn normal_pdf_f32(x: f32) -> f32 {
+    INV_SQRT_2PI * (-0.5 * x * x).exp()
+}
+
+fn gelu_grad(x: f32) -> f32 {
+    // Phi(x) + x * phi(x)
+    normal_cdf_f32(x) + x * normal_pdf_f32(x)
+}
+
+fn gelu_tanh_f32(x: f32) -> f32 {
+    // 0.5 x (1 + tanh(u)) == x * sigmoid(2u), which does not cancel in the negative tail
+    x / (1.0 + (-2.0 * GELU_TANH_C * (x + GELU_TANH_K * x * x * x)).exp())
+}
+
+fn gelu_tanh_grad(x: f32) -> f32 {
+    let t = (GELU_TANH_C * (x + GELU_TANH_K * x * x * x)).tanh();
+    0.5 * (1.0 + t) + 0.5 * x * (1.0 - t * t) * GELU_TANH_C * (1.0 + 3.0 * GELU_TANH_K * x * x)
+}
 
 #[derive(Debug)]
 pub struct ReluNode {
     input: Tensor,
-    output_cache: Tensor, // Need output for backward: grad = grad_output * (output > 0)
 }
 
 impl Node for ReluNode {
@@ -14,48 +155,561 @@ impl Node for ReluNode {
 
     fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
         // grad_input = grad * (input > 0)
-        // We can use output > 0 too (since relu(x) = x if x > 0 else 0)
-        
-        let grad_input = Tensor::zeros(grad.shape.clone(), grad.dtype);
-        
-        if grad.dtype == DType::F32 {
-            unsafe {
-                 let g_ptr = grad.storage.as_ptr().add(grad.offset) as *const f32;
-                 let out_ptr = self.output_cache.storage.as_ptr().add(self.output_cache.offset) as *const f32;
-                 let gi_ptr = grad_input.storage.as_ptr() as *mut f32;
-                 
-                 for i in 0..grad.numel() {
-                     let mask = if *out_ptr.add(i) > 0.0 { 1.0 } else { 0.0 };
-                     *gi_ptr.add(i) = *g_ptr.add(i) * mask;
-                 }
-            }
-        }
-        
-        vec![grad_input]
+        vec![chain(grad, &self.input, |x| if x > 0.0 { 1.0 } else { 0.0 })]
     }
 }
 
+/// `max(x, 0)`.
+pub fn try_relu(input: &Tensor) -> Result<Tensor> {
+    let output = try_map_unary(input, |val| if val > 0.0 { val } else { 0.0 })?;
+    Ok(record(output, input, || Arc::new(ReluNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_relu`].
 pub fn relu(input: &Tensor) -> Tensor {
-    let output = Tensor::zeros(input.shape.clone(), input.dtype);
-     
-    if input.dtype == DType::F32 {
-        unsafe {
-             let in_ptr = input.storage.as_ptr().add(input.offset) as *const f32;
-             let out_ptr = output.storage.as_ptr() as *mut f32;
-             
-             for i in 0..input.numel() {
-                 let val = *in_ptr.add(i);
-                 *out_ptr.add(i) = if val > 0.0 { val } else { 0.0 };
-             }
-        }
-    }
-    
-    if input.requires_grad {
-        let mut out = output.clone();
-        out.requires_grad = true;
-        out.ctx = Some(Box::new(ReluNode { input: input.clone(), output_cache: output }));
-        return out;
+    try_relu(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct LeakyReluNode {
+    input: Tensor,
+    negative_slope: f32,
+}
+
+impl Node for LeakyReluNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
     }
-    
-    output
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        let slope = self.negative_slope;
+        vec![chain(grad, &self.input, |x| if x > 0.0 { 1.0 } else { slope })]
+    }
+}
+
+/// `x` for positive inputs, `negative_slope * x` otherwise.
+pub fn try_leaky_relu(input: &Tensor, negative_slope: f32) -> Result<Tensor> {
+    let output = pointwise(input, |x| if x > 0.0 { x } else { negative_slope * x }, Some(Kernel::LeakyRelu(negative_slope)))?;
+    Ok(record(output, input, || Arc::new(LeakyReluNode { input: input.clone(), negative_slope })))
+}
+
+/// Panicking form of [`try_leaky_relu`].
+pub fn leaky_relu(input: &Tensor, negative_slope: f32) -> Tensor {
+    try_leaky_relu(input, negative_slope).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct SigmoidNode {
+    input: Tensor,
+}
+
+impl Node for SigmoidNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        let df = |x: f32| {
+            let s = sigmoid_f32(x);
+            s * (1.0 - s)
+        };
+        vec![chain_smooth(grad, &self.input, df, |x| {
+            let s = sigmoid(x);
+            mul(&s, &one_minus(&s))
+        })]
+    }
+}
+
+/// `1 / (1 + exp(-x))`.
+pub fn try_sigmoid(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, sigmoid_f32, Some(Kernel::Sigmoid))?;
+    Ok(record(output, input, || Arc::new(SigmoidNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_sigmoid`].
+pub fn sigmoid(input: &Tensor) -> Tensor {
+    try_sigmoid(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct TanhNode {
+    input: Tensor,
+}
+
+impl Node for TanhNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![chain_smooth(grad, &self.input, |x| 1.0 - x.tanh() * x.tanh(), |x| {
+            let t = tanh(x);
+            one_minus(&mul(&t, &t))
+        })]
+    }
+}
+
+/// Hyperbolic tangent.
+pub fn try_tanh(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, f32::tanh, Some(Kernel::Tanh))?;
+    Ok(record(output, input, || Arc::new(TanhNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_tanh`].
+pub fn tanh(input: &Tensor) -> Tensor {
+    try_tanh(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct SiluNode {
+    input: Tensor,
+}
+
+impl Node for SiluNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        let df = |x: f32| {
+            let s = sigmoid_f32(x);
+            s * (1.0 + x * (1.0 - s))
+        };
+        // s + x s (1 - s)
+        vec![chain_smooth(grad, &self.input, df, |x| {
+            let s = sigmoid(x);
+            add(&s, &mul(&mul(x, &s), &one_minus(&s)))
+        })]
+    }
+}
+
+/// `x * sigmoid(x)` (a.k.a. swish), the gate of SwiGLU.
+pub fn try_silu(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, |x| x * sigmoid_f32(x), Some(Kernel::Silu))?;
+    Ok(record(output, input, || Arc::new(SiluNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_silu`].
+pub fn silu(input: &Tensor) -> Tensor {
+    try_silu(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct NormalCdfNode {
+    input: Tensor,
+}
+
+impl Node for NormalCdfNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![chain_smooth(grad, &self.input, normal_pdf_f32, normal_pdf)]
+    }
+}
+
+/// Standard normal CDF `Phi(x)`, the derivative of GELU's gate. Recorded so GELU
+/// gradients differentiate to any order.
+fn normal_cdf(input: &Tensor) -> Tensor {
+    record(map_unary(input, normal_cdf_f32), input, || Arc::new(NormalCdfNode { input: input.clone() }))
+}
+
+/// Standard normal density `exp(-x^2 / 2) / sqrt(2 pi)`, from recorded ops.
+fn normal_pdf(x: &Tensor) -> Tensor {
+    mul_scalar(&exp(&mul_scalar(&mul(x, x), -0.5)), INV_SQRT_2PI)
+}
+
+#[derive(Debug)]
+pub struct GeluNode {
+    input: Tensor,
+}
+
+impl Node for GeluNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![chain_smooth(grad, &self.input, gelu_grad, |x| add(&normal_cdf(x), &mul(x, &normal_pdf(x))))]
+    }
+}
+
+/// Exact GELU, `x * Phi(x) = 0.5 x (1 + erf(x / sqrt(2)))`.
+pub fn try_gelu(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, gelu_f32, Some(Kernel::Gelu))?;
+    Ok(record(output, input, || Arc::new(GeluNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_gelu`].
+pub fn gelu(input: &Tensor) -> Tensor {
+    try_gelu(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct GeluTanhNode {
+    input: Tensor,
+}
+
+impl Node for GeluTanhNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![chain_smooth(grad, &self.input, gelu_tanh_grad, |x| {
+            // 0.5 (1 + t) + 0.5 C x (1 - t^2) (1 + 3 K x^2), t = tanh(C (x + K x^3))
+            let x2 = mul(x, x);
+            let t = tanh(&mul_scalar(&add(x, &mul_scalar(&mul(&x2, x), GELU_TANH_K)), GELU_TANH_C));
+            let inner = add_scalar(&mul_scalar(&x2, 3.0 * GELU_TANH_K), 1.0);
+            let slope = mul(&mul(x, &one_minus(&mul(&t, &t))), &inner);
+            add(&mul_scalar(&add_scalar(&t, 1.0), 0.5), &mul_scalar(&slope, 0.5 * GELU_TANH_C))
+        })]
+    }
+}
+
+/// GELU with the tanh approximation used by GPT-2 style models:
+/// `0.5 x (1 + tanh(sqrt(2/pi) (x + 0.044715 x^3)))`.
+pub fn try_gelu_tanh(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, gelu_tanh_f32, Some(Kernel::GeluTanh))?;
+    Ok(record(output, input, || Arc::new(GeluTanhNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_gelu_tanh`].
+pub fn gelu_tanh(input: &Tensor) -> Tensor {
+    try_gelu_tanh(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct SoftplusNode {
+    input: Tensor,
+    beta: f32,
+}
+
+impl Node for SoftplusNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        let beta = self.beta;
+        vec![chain_smooth(grad, &self.input, |x| sigmoid_f32(beta * x), |x| sigmoid(&mul_scalar(x, beta)))]
+    }
+}
+
+/// `ln(1 + exp(beta * x)) / beta`, a smooth ReLU. Returns `x` once `beta * x > 20`,
+/// where the two agree to F32 precision.
+pub fn try_softplus(input: &Tensor, beta: f32) -> Result<Tensor> {
+    let f = |x: f32| {
+        let bx = beta * x;
+        if bx > 20.0 { x } else { bx.exp().ln_1p() / beta }
+    };
+    let output = pointwise(input, f, Some(Kernel::Softplus(beta)))?;
+    Ok(record(output, input, || Arc::new(SoftplusNode { input: input.clone(), beta })))
+}
+
+/// Panicking form of [`try_softplus`].
+pub fn softplus(input: &Tensor, beta: f32) -> Tensor {
+    try_softplus(input, beta).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Backward passes below save the detached output for the fast path. When a graph is being
+// recorded (create_graph) they recompute the derivative from the input with these same ops,
+// so it stays differentiable to any order.
+
+#[derive(Debug)]
+pub struct ExpNode {
+    input: Tensor,
+    output: Tensor, // Detached result
+}
+
+impl Node for ExpNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        if recording(grad, &self.input) {
+            return vec![mul(grad, &exp(&self.input))];
+        }
+        vec![map_binary(grad, &self.output, |g, y| g * y)]
+    }
+}
+
+/// `e^x`.
+pub fn try_exp(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, f32::exp, Some(Kernel::Exp))?;
+    Ok(record(output.clone(), input, || Arc::new(ExpNode { input: input.clone(), output: output.detach() })))
+}
+
+/// Panicking form of [`try_exp`].
+pub fn exp(input: &Tensor) -> Tensor {
+    try_exp(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct LogNode {
+    input: Tensor,
+}
+
+impl Node for LogNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        if recording(grad, &self.input) {
+            return vec![div(grad, &self.input)];
+        }
+        vec![map_binary(grad, &self.input, |g, x| g / x)]
+    }
+}
+
+/// Natural logarithm. NaN for negative inputs, `-inf` at 0.
+pub fn try_log(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, f32::ln, Some(Kernel::Log))?;
+    Ok(record(output, input, || Arc::new(LogNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_log`].
+pub fn log(input: &Tensor) -> Tensor {
+    try_log(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct SqrtNode {
+    input: Tensor,
+    output: Tensor, // Detached result
+}
+
+impl Node for SqrtNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        if recording(grad, &self.input) {
+            return vec![div(grad, &mul_scalar(&sqrt(&self.input), 2.0))];
+        }
+        vec![map_binary(grad, &self.output, |g, y| g / (2.0 * y))]
+    }
+}
+
+/// Square root. NaN for negative inputs.
+pub fn try_sqrt(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, f32::sqrt, Some(Kernel::Sqrt))?;
+    Ok(record(output.clone(), input, || Arc::new(SqrtNode { input: input.clone(), output: output.detach() })))
+}
+
+/// Panicking form of [`try_sqrt`].
+pub fn sqrt(input: &Tensor) -> Tensor {
+    try_sqrt(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct RsqrtNode {
+    input: Tensor,
+    output: Tensor, // Detached result
+}
+
+impl Node for RsqrtNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // d/dx x^(-1/2) = -0.5 * y / x = -0.5 * y^3
+        if recording(grad, &self.input) {
+            return vec![mul_scalar(&mul(grad, &div(&rsqrt(&self.input), &self.input)), -0.5)];
+        }
+        vec![map_binary(grad, &self.output, |g, y| -0.5 * g * y * y * y)]
+    }
+}
+
+/// `1 / sqrt(x)`, as used by RMSNorm and LayerNorm.
+pub fn try_rsqrt(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, |x| 1.0 / x.sqrt(), Some(Kernel::Rsqrt))?;
+    Ok(record(output.clone(), input, || Arc::new(RsqrtNode { input: input.clone(), output: output.detach() })))
+}
+
+/// Panicking form of [`try_rsqrt`].
+pub fn rsqrt(input: &Tensor) -> Tensor {
+    try_rsqrt(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct AbsNode {
+    input: Tensor,
+}
+
+impl Node for AbsNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // Subgradient 0 at x == 0
+        vec![chain(grad, &self.input, |x| if x > 0.0 { 1.0 } else if x < 0.0 { -1.0 } else { 0.0 })]
+    }
+}
+
+/// Absolute value.
+pub fn try_abs(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, f32::abs, Some(Kernel::Abs))?;
+    Ok(record(output, input, || Arc::new(AbsNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_abs`].
+pub fn abs(input: &Tensor) -> Tensor {
+    try_abs(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct NegNode {
+    input: Tensor,
+}
+
+impl Node for NegNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![neg(grad)]
+    }
+}
+
+/// `-x`.
+pub fn try_neg(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, |x| -x, Some(Kernel::Neg))?;
+    Ok(record(output, input, || Arc::new(NegNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_neg`].
+pub fn neg(input: &Tensor) -> Tensor {
+    try_neg(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct SinNode {
+    input: Tensor,
+}
+
+impl Node for SinNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        if recording(grad, &self.input) {
+            return vec![mul(grad, &cos(&self.input))];
+        }
+        vec![map_binary(grad, &self.input, |g, x| g * x.cos())]
+    }
+}
+
+/// Sine, in radians. The fast kernel is accurate for |x| up to ~8192.
+pub fn try_sin(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, f32::sin, Some(Kernel::Sin))?;
+    Ok(record(output, input, || Arc::new(SinNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_sin`].
+pub fn sin(input: &Tensor) -> Tensor {
+    try_sin(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct CosNode {
+    input: Tensor,
+}
+
+impl Node for CosNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        if recording(grad, &self.input) {
+            return vec![neg(&mul(grad, &sin(&self.input)))];
+        }
+        vec![map_binary(grad, &self.input, |g, x| -g * x.sin())]
+    }
+}
+
+/// Cosine, in radians. The fast kernel is accurate for |x| up to ~8192.
+pub fn try_cos(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, f32::cos, Some(Kernel::Cos))?;
+    Ok(record(output, input, || Arc::new(CosNode { input: input.clone() })))
+}
+
+/// Panicking form of [`try_cos`].
+pub fn cos(input: &Tensor) -> Tensor {
+    try_cos(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct ClampNode {
+    input: Tensor,
+    min: f32,
+    max: f32,
+}
+
+impl Node for ClampNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // Gradient passes where the input was inside [min, max], bounds included
+        let (min, max) = (self.min, self.max);
+        vec![chain(grad, &self.input, |x| if (min..=max).contains(&x) { 1.0 } else { 0.0 })]
+    }
+}
+
+/// Clamps every element into `[min, max]`; NaN stays NaN.
+///
+/// Returns `InvalidArgument` if `min > max` or either bound is NaN.
+pub fn try_clamp(input: &Tensor, min: f32, max: f32) -> Result<Tensor> {
+    if min.is_nan() || max.is_nan() || min > max {
+        return Err(TensorError::InvalidArgument(format!("clamp: min ({}) must not exceed max ({})", min, max)));
+    }
+    let output = pointwise(input, |x| x.clamp(min, max), Some(Kernel::Clamp(min, max)))?;
+    Ok(record(output, input, || Arc::new(ClampNode { input: input.clone(), min, max })))
+}
+
+/// Panicking form of [`try_clamp`].
+pub fn clamp(input: &Tensor, min: f32, max: f32) -> Tensor {
+    try_clamp(input, min, max).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug)]
+pub struct ReciprocalNode {
+    input: Tensor,
+    output: Tensor, // Detached result
+}
+
+impl Node for ReciprocalNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        if recording(grad, &self.input) {
+            let y = reciprocal(&self.input);
+            return vec![neg(&mul(grad, &mul(&y, &y)))];
+        }
+        vec![map_binary(grad, &self.output, |g, y| -g * y * y)]
+    }
+}
+
+/// `1 / x`.
+pub fn try_reciprocal(input: &Tensor) -> Result<Tensor> {
+    let output = pointwise(input, f32::recip, Some(Kernel::Reciprocal))?;
+    Ok(record(output.clone(), input, || Arc::new(ReciprocalNode { input: input.clone(), output: output.detach() })))
+}
+
+/// Panicking form of [`try_reciprocal`].
+pub fn reciprocal(input: &Tensor) -> Tensor {
+    try_reciprocal(input).unwrap_or_else(|e| panic!("{}", e))
 }
@@ -0,0 +1,99 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use crate::error::Result;
+
+// Worker threads used by the parallel kernels (GEMM, elementwise, reductions, softmax).
+// By default they run on rayon's global pool, one thread per core. `set_num_threads` swaps in
+// a dedicated pool for the whole process; `with_thread_pool` overrides it for one calling
+// thread, e.g. to pin the engine to a pool built on the big cores while the UI thread stays free.
+
+/// Kernels over at least this many elements split their work across the pool.
+pub(crate) const PARALLEL_THRESHOLD: usize = 1 << 15;
+
+static POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+
+thread_local! {
+    static SCOPED: RefCell<Option<Arc<ThreadPool>>> = const { RefCell::new(None) };
+}
+
+/// Runs the engine on a dedicated pool of `n` threads, or on rayon's global pool when `n == 0`.
+/// Calls already in flight finish on the pool they started on.
+pub fn set_num_threads(n: usize) -> Result<()> {
+    let pool = match n {
+        0 => None,
+        n => Some(Arc::new(
+            ThreadPoolBuilder::new().num_threads(n).thread_name(|i| format!("edge-tensor-{}", i)).build()?,
+        )),
+    };
+    *POOL.write().unwrap() = pool;
+    Ok(())
+}
+
+/// The pool a call from this thread runs on, if it isn't rayon's global one.
+fn current_pool() -> Option<Arc<ThreadPool>> {
+    SCOPED.with(|s| s.borrow().clone()).or_else(|| POOL.read().unwrap().clone())
+}
+
+/// Number of threads kernels called from this thread may use.
+pub fn num_threads() -> usize {
+    if rayon::current_thread_index().is_some() {
+        return rayon::current_num_threads();
+    }
+    current_pool().map_or_else(rayon::current_num_threads, |p| p.current_num_threads())
+}
+
+/// RAII guard returned by [`with_thread_pool`]. Not `Send`: the override is per-thread.
+pub struct ThreadPoolGuard {
+    prev: Option<Arc<ThreadPool>>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ThreadPoolGuard {
+    fn drop(&mut self) {
+        SCOPED.with(|s| *s.borrow_mut() = self.prev.take());
+    }
+}
+
+/// Runs kernels called from this thread on `pool` until the guard is dropped, taking
+/// precedence over [`set_num_threads`].
+pub fn with_thread_pool(pool: Arc<ThreadPool>) -> ThreadPoolGuard {
+    let prev = SCOPED.with(|s| s.borrow_mut().replace(pool));
+    ThreadPoolGuard { prev, _not_send: PhantomData }
+}
+
+/// Runs `op` on the engine's pool. Inside a rayon worker it runs in place, so nested
+/// parallel kernels share the pool they were called from.
+///
+/// `op` runs on another thread: thread-local settings (grad mode, precision) must be read
+/// by the caller beforehand.
+pub(crate) fn install<R: Send>(op: impl FnOnce() -> R + Send) -> R {
+    if rayon::current_thread_index().is_some() {
+        return op();
+    }
+    match current_pool() {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+/// Whether a kernel over `numel` elements should split its work.
+pub(crate) fn should_parallelize(numel: usize) -> bool {
+    numel >= PARALLEL_THRESHOLD && num_threads() > 1
+}
+
+/// Raw pointer that may be shared with workers writing disjoint elements.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SyncPtr<T>(pub *mut T);
+
+unsafe impl<T> Send for SyncPtr<T> {}
+unsafe impl<T> Sync for SyncPtr<T> {}
+
+impl<T> SyncPtr<T> {
+    /// Accessor, so closures capture the whole wrapper rather than the raw field.
+    #[inline(always)]
+    pub(crate) fn get(self) -> *mut T {
+        self.0
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use half::f16;
+use crate::error::{Result, TensorError};
+use crate::ops::strided::for_each_offset;
+use crate::tensor::{Tensor, DType};
+
+/// Rust element types that map onto a storage `DType`.
+pub trait Element: Copy + Send + Sync + 'static {
+    const DTYPE: DType;
+}
+
+impl Element for f32 {
+    const DTYPE: DType = DType::F32;
+}
+
+impl Element for f16 {
+    const DTYPE: DType = DType::F16;
+}
+
+impl Element for i8 {
+    const DTYPE: DType = DType::I8;
+}
+
+impl Element for i64 {
+    const DTYPE: DType = DType::I64;
+}
+
+// Safe, dtype-checked data access.
+// All readers honor `offset` and `strides`, so they work on any view.
+
+impl Tensor {
+    fn check_dtype<T: Element>(&self) -> Result<()> {
+        if self.dtype != T::DTYPE {
+            return Err(TensorError::DTypeMismatch { expected: T::DTYPE, found: self.dtype });
+        }
+        Ok(())
+    }
+
+    /// Element offset (not bytes) of `index`, relative to `offset`.
+    fn element_offset(&self, index: &[usize]) -> Result<usize> {
+        if index.len() != self.shape.len() || index.iter().zip(self.shape.iter()).any(|(&i, &d)| i >= d) {
+            return Err(TensorError::OutOfBounds { index: index.to_vec(), shape: self.shape.clone() });
+        }
+        Ok(index.iter().zip(self.strides.iter()).map(|(i, s)| i * s).sum())
+    }
+
+    /// Copies the elements out in row-major order.
+    pub fn to_vec<T: Element>(&self) -> Result<Vec<T>> {
+        self.check_dtype::<T>()?;
+        let mut out = Vec::with_capacity(self.numel());
+        unsafe {
+            let ptr = self.storage.as_ptr().add(self.offset) as *const T;
+            for_each_offset(&self.shape, [&self.strides], |_, [off]| out.push(*ptr.add(off)));
+        }
+        Ok(out)
+    }
+
+    /// Copies the elements of an F32 tensor out in row-major order.
+    pub fn to_vec_f32(&self) -> Result<Vec<f32>> {
+        self.to_vec::<f32>()
+    }
+
+    /// Borrows the elements of a contiguous tensor without copying.
+    pub fn as_slice<T: Element>(&self) -> Result<&[T]> {
+        self.check_dtype::<T>()?;
+        if !self.is_contiguous() {
+            return Err(TensorError::NotContiguous);
+        }
+        unsafe {
+            let ptr = self.storage.as_ptr().add(self.offset) as *const T;
+            Ok(std::slice::from_raw_parts(ptr, self.numel()))
+        }
+    }
+
+    /// Returns the value of a single-element tensor.
+    pub fn item<T: Element>(&self) -> Result<T> {
+        if self.numel() != 1 {
+            return Err(TensorError::ShapeMismatch { expected: vec![], found: self.shape.clone() });
+        }
+        self.get(&vec![0; self.shape.len()])
+    }
+
+    /// Reads the element at `index`.
+    pub fn get<T: Element>(&self, index: &[usize]) -> Result<T> {
+        self.check_dtype::<T>()?;
+        let off = self.element_offset(index)?;
+        unsafe { Ok(*(self.storage.as_ptr().add(self.offset) as *const T).add(off)) }
+    }
+
+    /// Writes the element at `index` in place. The write is not recorded in the graph.
+    ///
+    /// # Safety
+    /// Clones and views share storage, so the caller must ensure that no slice borrowed with
+    /// [`as_slice`](Self::as_slice) covers the element and that no other thread accesses it
+    /// during the write.
+    pub unsafe fn set<T: Element>(&self, index: &[usize], value: T) -> Result<()> {
+        self.check_dtype::<T>()?;
+        let off = self.element_offset(index)?;
+        *(self.storage.as_ptr().add(self.offset) as *mut T).add(off) = value;
+        Ok(())
+    }
+
+    /// Replaces this handle's storage with a private dense copy if any other tensor shares it,
+    /// so in-place writes through `&mut self` cannot be observed elsewhere.
+    pub(crate) fn make_unique(&mut self) {
+        if Arc::strong_count(&self.storage) > 1 {
+            let owned = Self::zeros(self.shape.clone(), self.dtype);
+            // `owned` is freshly allocated, so nothing else can observe the write
+            unsafe { owned.copy_from(self) };
+            self.storage = owned.storage;
+            self.strides = owned.strides;
+            self.offset = 0;
+        }
+    }
+}
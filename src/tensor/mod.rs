@@ -1,9 +1,13 @@
+pub mod access;
 pub mod storage;
 pub mod tensor_impl;
+pub mod view;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 pub mod tests;
 
+pub use access::Element;
 pub use storage::Storage;
-pub use tensor_impl::{Tensor, DType, Shape, Strides};
+pub use tensor_impl::{Tensor, DType, GradHook, Shape, Strides};
 
@@ -1,7 +1,10 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 use crate::tensor::storage::{Storage, next_uid};
 use crate::autograd::node::Node;
+use crate::ops::strided::for_each_offset;
 use std::fmt;
+use half::f16;
 
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -10,6 +13,7 @@ pub enum DType {
     F16,
     I8, // Quantized
     I4, // Packed quantized
+    I64, // Indices (argmax / argmin)
 }
 
 impl DType {
@@ -18,6 +22,7 @@ impl DType {
             DType::F32 => 4,
             DType::F16 => 2,
             DType::I8 => 1,
+            DType::I64 => 8,
             DType::I4 => 0, // Special handling needed usually, but packed means 0.5 bytes? 
                             // Usually we just say block size dictates byte size. 
                             // For indexing, we might say 1 byte contains 2 I4s.
@@ -28,6 +33,9 @@ impl DType {
 pub type Shape = Vec<usize>;
 pub type Strides = Vec<usize>;
 
+/// Callback run on a tensor's gradient during backward; returning `Some` replaces the gradient.
+pub type GradHook = Box<dyn Fn(&Tensor) -> Option<Tensor> + Send + Sync>;
+
 pub struct Tensor {
     pub(crate) id: usize,
     pub(crate) shape: Shape,
@@ -37,9 +45,14 @@ pub struct Tensor {
     pub(crate) dtype: DType,
     
     // Autograd
+    // `id`, `grad`, `ctx`, `hooks` and `retains_grad` define the tensor's identity in the graph:
+    // clones share them, so gradients accumulated through any handle (e.g. a Node's saved parent)
+    // reach the original.
     pub(crate) requires_grad: bool,
     pub(crate) grad: Arc<RwLock<Option<Tensor>>>,
-    pub(crate) ctx: Option<Box<dyn Node>>,
+    pub(crate) ctx: Option<Arc<dyn Node>>,
+    pub(crate) hooks: Arc<RwLock<Vec<GradHook>>>,
+    pub(crate) retains_grad: Arc<AtomicBool>,
 }
 
 impl Tensor {
@@ -61,6 +74,8 @@ impl Tensor {
             requires_grad,
             grad: Arc::new(RwLock::new(None)),
             ctx: None,
+            hooks: Arc::new(RwLock::new(Vec::new())),
+            retains_grad: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -81,6 +96,51 @@ impl Tensor {
         self.dtype
     }
 
+    pub fn requires_grad(&self) -> bool {
+        self.requires_grad
+    }
+
+    /// Marks this handle as a leaf that should (or should not) receive gradients.
+    pub fn requires_grad_(&mut self, requires_grad: bool) -> &mut Self {
+        self.requires_grad = requires_grad;
+        self
+    }
+
+    /// Returns a handle to the same data cut off from the graph:
+    /// fresh identity, no gradient buffer contents, no Node, `requires_grad == false`.
+    pub fn detach(&self) -> Self {
+        Self::new(self.storage.clone(), self.shape.clone(), self.strides.clone(), self.offset, self.dtype, false)
+    }
+
+    /// Returns a handle to the accumulated gradient, if any.
+    /// Only leaves with `requires_grad` and tensors marked with [`retain_grad`](Self::retain_grad)
+    /// keep their gradient after backward.
+    pub fn grad(&self) -> Option<Tensor> {
+        self.grad.read().unwrap().clone()
+    }
+
+    /// Keeps the gradient of this non-leaf tensor after backward, readable through [`grad`](Self::grad).
+    /// Leaves already retain theirs, so this is a no-op for them.
+    pub fn retain_grad(&self) {
+        self.retains_grad.store(true, Ordering::Relaxed);
+    }
+
+    /// True if backward stores this tensor's gradient: requires_grad leaves and retained non-leaves.
+    pub fn retains_grad(&self) -> bool {
+        self.requires_grad && (self.ctx.is_none() || self.retains_grad.load(Ordering::Relaxed))
+    }
+
+    /// Registers `hook` to run on this tensor's gradient during backward, once the gradient
+    /// from the current pass is complete and before it is propagated to inputs or stored.
+    /// Hooks run in registration order; a hook returning `Some(grad)` replaces the gradient
+    /// seen by later hooks and by the rest of the pass (e.g. for clipping).
+    pub fn register_hook<F>(&self, hook: F)
+    where
+        F: Fn(&Tensor) -> Option<Tensor> + Send + Sync + 'static,
+    {
+        self.hooks.write().unwrap().push(Box::new(hook));
+    }
+
     pub fn is_contiguous(&self) -> bool {
         // Simple check: stride[i] == stride[i+1] * shape[i+1]
         if self.shape.is_empty() { return true; }
@@ -109,42 +169,66 @@ impl Tensor {
         self.shape.iter().product()
     }
 
+    /// Computes the NumPy-style broadcast of two shapes.
+    /// Dimensions are aligned from the right; a dim of size 1 stretches to match the other.
+    /// Returns `None` if the shapes are incompatible.
+    pub fn broadcast_shape(lhs: &[usize], rhs: &[usize]) -> Option<Shape> {
+        let ndim = lhs.len().max(rhs.len());
+        let mut out = vec![0; ndim];
+        for i in 0..ndim {
+            // Missing leading dims behave like size 1
+            let l = if i < ndim - lhs.len() { 1 } else { lhs[i - (ndim - lhs.len())] };
+            let r = if i < ndim - rhs.len() { 1 } else { rhs[i - (ndim - rhs.len())] };
+            out[i] = match (l, r) {
+                (l, r) if l == r => l,
+                (1, r) => r,
+                (l, 1) => l,
+                _ => return None,
+            };
+        }
+        Some(out)
+    }
+
+    /// Strides that read this tensor as if it had `shape`.
+    /// Broadcast dims (new leading dims and stretched size-1 dims) get stride 0.
+    pub(crate) fn broadcast_strides(&self, shape: &[usize]) -> Strides {
+        assert!(shape.len() >= self.shape.len(), "Cannot broadcast {:?} to {:?}", self.shape, shape);
+        let lead = shape.len() - self.shape.len();
+        let mut strides = vec![0; shape.len()];
+        for (i, (&dim, &stride)) in self.shape.iter().zip(self.strides.iter()).enumerate() {
+            let target = shape[lead + i];
+            if dim == target {
+                strides[lead + i] = stride;
+            } else {
+                assert_eq!(dim, 1, "Cannot broadcast {:?} to {:?}", self.shape, shape);
+            }
+        }
+        strides
+    }
+
     pub fn t(&self) -> Self {
         assert!(self.shape.len() >= 2, "Transpose requires at least 2 dimensions");
         let ndim = self.shape.len();
-        let mut new_shape = self.shape.clone();
-        let mut new_strides = self.strides.clone();
-        
         // Swap last two dimensions for simple 2D transpose or multi-dim last-two swap
-        new_shape.swap(ndim - 1, ndim - 2);
-        new_strides.swap(ndim - 1, ndim - 2);
-        
-        Self {
-            id: next_uid(),
-            shape: new_shape,
-            strides: new_strides,
-            storage: self.storage.clone(),
-            offset: self.offset,
-            dtype: self.dtype,
-            requires_grad: self.requires_grad,
-            grad: Arc::new(RwLock::new(None)),
-            ctx: None,
-        }
+        self.transpose(ndim - 2, ndim - 1)
     }
 }
 
 impl Clone for Tensor {
+    /// Cheap handle copy: shares storage, gradient buffer and graph node.
     fn clone(&self) -> Self {
         Self {
-            id: next_uid(),
+            id: self.id,
             shape: self.shape.clone(),
             strides: self.strides.clone(),
             storage: self.storage.clone(),
             offset: self.offset,
             dtype: self.dtype,
             requires_grad: self.requires_grad,
-            grad: Arc::new(RwLock::new(None)), // New tensor, new grad buffer
-            ctx: None,
+            grad: self.grad.clone(), // Same tensor, same grad buffer
+            ctx: self.ctx.clone(),
+            hooks: self.hooks.clone(),
+            retains_grad: self.retains_grad.clone(),
         }
     }
 }
@@ -178,11 +262,15 @@ impl Tensor {
 
     pub fn ones(shape: Shape, dtype: DType) -> Self {
         let t = Self::zeros(shape, dtype);
-        // Fill with 1. Need ops::fill generic.
-        // Prototyping: just F32 support for now
-        if dtype == DType::F32 {
-            let data = unsafe { std::slice::from_raw_parts_mut(t.storage.as_ptr() as *mut f32, t.numel()) };
-            for x in data.iter_mut() { *x = 1.0; }
+        let numel = t.numel();
+        unsafe {
+            match dtype {
+                DType::F32 => std::slice::from_raw_parts_mut(t.storage.as_ptr() as *mut f32, numel).fill(1.0),
+                DType::F16 => std::slice::from_raw_parts_mut(t.storage.as_ptr() as *mut f16, numel).fill(f16::ONE),
+                DType::I8 => std::slice::from_raw_parts_mut(t.storage.as_ptr() as *mut i8, numel).fill(1),
+                DType::I64 => std::slice::from_raw_parts_mut(t.storage.as_ptr() as *mut i64, numel).fill(1),
+                DType::I4 => {} // Packed nibbles have no per-element byte to fill
+            }
         }
         t
     }
@@ -201,40 +289,56 @@ impl Tensor {
     }
 
     /// Internal helper to accumulate gradient.
+    /// The stored gradient is always an owned, contiguous tensor, so `grad` may be any view
+    /// (or alias other tensors) without later accumulations writing through to it.
     pub fn add_grad(&self, grad: Tensor) {
+        assert_eq!(grad.shape, self.shape, "Gradient shape mismatch");
+
         // lock is RwLock<Option<Tensor>>
         let mut lock = self.grad.write().unwrap();
-        
-        if let Some(existing_grad) = lock.as_mut() {
-             // Perform existing_grad += grad
-             // Naive F32 loop
-             // In reality we should use the `Add` op kernel here? 
-             // But `Add` op returns a NEW tensor and records to graph.
-             // Here we just want raw in-place addition.
-             if self.dtype == DType::F32 {
-                 // Storage is Arc<Storage>. We use as_ptr() and then cast to *mut. 
-                 // This is technically interior mutability at the raw pointer level.
-                 let lhs_ptr = self.storage.as_ptr() as *mut f32;
-                 let rhs_ptr = grad.storage.as_ptr() as *const f32;
-                 
-                 let lhs = unsafe { std::slice::from_raw_parts_mut(lhs_ptr.add(existing_grad.offset / 4), existing_grad.numel()) };
-                 let rhs = unsafe { std::slice::from_raw_parts(rhs_ptr.add(grad.offset / 4), grad.numel()) };
-                 
-                 if existing_grad.is_contiguous() && grad.is_contiguous() {
-                     for (l, r) in lhs.iter_mut().zip(rhs.iter()) {
-                         *l += *r;
-                     }
-                 } else {
-                     panic!("Gradient accumulation for non-contiguous tensors not yet implemented");
-                 }
-             }
+        Self::accumulate_grad(&mut lock, grad);
+    }
+
+    /// Accumulates `grad` into `slot`; also used by backward for gradients it does not store.
+    pub(crate) fn accumulate_grad(slot: &mut Option<Tensor>, grad: Tensor) {
+        if slot.as_ref().is_some_and(|g| g.requires_grad) {
+            // A gradient recorded with `create_graph` is part of a graph: sum into a private copy
+            let owned = Self::zeros(grad.shape.clone(), grad.dtype);
+            unsafe { owned.copy_from(slot.as_ref().unwrap()) };
+            *slot = Some(owned);
+        }
+        if let Some(existing_grad) = slot.as_mut() {
+            // Handles read from `.grad` share its storage: sum into a private copy if any is alive
+            existing_grad.make_unique();
+            let existing_grad = &*existing_grad;
+            // Perform existing_grad += grad, in place in the gradient's own storage.
+            // Raw in-place addition rather than the `add` op, which would allocate and record to graph.
+            assert_eq!(existing_grad.shape, grad.shape, "Gradient shape mismatch");
+            assert_eq!(existing_grad.dtype, grad.dtype, "Gradient dtype mismatch");
+            unsafe {
+                let dst = existing_grad.storage.as_ptr().add(existing_grad.offset) as *mut u8;
+                let src = grad.storage.as_ptr().add(grad.offset);
+                let strides = [existing_grad.strides.as_slice(), grad.strides.as_slice()];
+                match grad.dtype {
+                    DType::F32 => {
+                        let (dst, src) = (dst as *mut f32, src as *const f32);
+                        for_each_offset(&grad.shape, strides, |_, [d, s]| *dst.add(d) += *src.add(s));
+                    }
+                    DType::F16 => {
+                        // Accumulate in F32 and round once per element
+                        let (dst, src) = (dst as *mut f16, src as *const f16);
+                        for_each_offset(&grad.shape, strides, |_, [d, s]| {
+                            *dst.add(d) = f16::from_f32((*dst.add(d)).to_f32() + (*src.add(s)).to_f32());
+                        });
+                    }
+                    dtype => panic!("Gradient accumulation not supported for {:?}", dtype),
+                }
+            }
         } else {
-            // First gradient. Just take it.
-            // Ideally we should clone the data of `grad` into a new Tensor that owns its storage,
-            // because `grad` passed in might be a view or temporary.
-            // For now, let's assume `grad` is a result of an op and we can just store it.
-            *lock = Some(grad);
+            // First gradient: take an owned dense copy, detached from any graph.
+            let owned = Self::zeros(grad.shape.clone(), grad.dtype);
+            unsafe { owned.copy_from(&grad) };
+            *slot = Some(owned);
         }
     }
 }
-
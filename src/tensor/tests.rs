@@ -1,7 +1,13 @@
 #[cfg(test)]
 mod tests {
     use crate::tensor::{Tensor, DType};
+    use crate::TensorError;
+    use crate::nn::kv_cache::KVCache;
+    use std::sync::Arc;
 
+    fn values(t: &Tensor) -> Vec<f32> {
+        t.to_vec_f32().unwrap()
+    }
 
     #[test]
     fn test_tensor_creation() {
@@ -15,13 +21,11 @@ mod tests {
     #[test]
     fn test_from_vec() {
         let data = vec![1.0, 2.0, 3.0, 4.0];
-        let t = Tensor::from_vec_f32(data.clone(), vec![2, 2]);
+        let t = Tensor::from_vec_f32(data, vec![2, 2]);
         
-        unsafe {
-            let ptr = t.storage.as_ptr() as *const f32;
-            assert_eq!(*ptr, 1.0);
-            assert_eq!(*ptr.add(3), 4.0);
-        }
+        let slice = t.as_slice::<f32>().unwrap();
+        assert_eq!(slice[0], 1.0);
+        assert_eq!(slice[3], 4.0);
     }
 
     #[test]
@@ -31,4 +35,167 @@ mod tests {
         assert_eq!(t_t.shape(), &[3, 2]);
         assert_eq!(t_t.strides(), &[1, 3]); // Swapped
     }
+
+    #[test]
+    fn test_broadcast_shape() {
+        assert_eq!(Tensor::broadcast_shape(&[2, 3], &[3]), Some(vec![2, 3]));
+        assert_eq!(Tensor::broadcast_shape(&[4, 1, 3], &[2, 1]), Some(vec![4, 2, 3]));
+        assert_eq!(Tensor::broadcast_shape(&[], &[5]), Some(vec![5]));
+        assert_eq!(Tensor::broadcast_shape(&[2, 3], &[2]), None);
+
+        let t = Tensor::zeros(vec![3, 1], DType::F32);
+        assert_eq!(t.broadcast_strides(&[2, 3, 4]), vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn test_view_reshape_permute() {
+        let t = Tensor::from_vec_f32((0..6).map(|x| x as f32).collect(), vec![2, 3]);
+
+        // Contiguous reshape is zero-copy
+        let r = t.reshape(&[3, 2]);
+        assert!(Arc::ptr_eq(&r.storage, &t.storage));
+        assert_eq!(r.strides(), &[2, 1]);
+
+        // Reshaping a transposed view has to copy
+        let r = t.t().reshape(&[6]);
+        assert!(!Arc::ptr_eq(&r.storage, &t.storage));
+        assert_eq!(values(&r), vec![0.0, 3.0, 1.0, 4.0, 2.0, 5.0]);
+
+        let p = t.unsqueeze(0).permute(&[2, 0, 1]);
+        assert_eq!(p.shape(), &[3, 1, 2]);
+        assert_eq!(values(&p), vec![0.0, 3.0, 1.0, 4.0, 2.0, 5.0]);
+        assert_eq!(p.squeeze(1).shape(), &[3, 2]);
+    }
+
+    #[test]
+    fn test_view_narrow_expand() {
+        let t = Tensor::from_vec_f32((0..12).map(|x| x as f32).collect(), vec![3, 4]);
+
+        let n = t.narrow(1, 1, 2);
+        assert_eq!(n.shape(), &[3, 2]);
+        assert_eq!(n.offset, 4);
+        assert_eq!(values(&n), vec![1.0, 2.0, 5.0, 6.0, 9.0, 10.0]);
+        assert_eq!(values(&t.slice(0, 2..3)), vec![8.0, 9.0, 10.0, 11.0]);
+
+        let e = Tensor::from_vec_f32(vec![1.0, 2.0], vec![2, 1]).expand(&[2, 3]);
+        assert_eq!(e.strides(), &[1, 0]);
+        assert_eq!(values(&e), vec![1.0, 1.0, 1.0, 2.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn test_view_backward() {
+        let mut t = Tensor::zeros(vec![3, 2], DType::F32);
+        t.requires_grad = true;
+
+        // Narrow scatters the gradient into the selected rows
+        let n = t.narrow(0, 1, 2);
+        let grad = Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]);
+        let g = n.ctx.as_ref().unwrap().backward(&grad);
+        assert_eq!(values(&g[0]), vec![0.0, 0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        // Expand sums the gradient over broadcast dims
+        let e = t.narrow(1, 0, 1).expand(&[3, 4]);
+        let g = e.ctx.as_ref().unwrap().backward(&Tensor::ones(vec![3, 4], DType::F32));
+        assert_eq!(g[0].shape(), &[3, 1]);
+        assert_eq!(values(&g[0]), vec![4.0, 4.0, 4.0]);
+
+        // Permute applies the inverse permutation
+        let p = t.t();
+        let g = p.ctx.as_ref().unwrap().backward(&Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2, 3]));
+        assert_eq!(values(&g[0]), vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    }
+
+    #[test]
+    fn test_kv_cache_view() {
+        let mut cache = KVCache::new(8, 2, 4);
+        let k = Tensor::ones(vec![3, 2, 4], DType::F32);
+        cache.update(&k, &k, 0);
+        let (k_view, v_view) = cache.get_view(cache.current_pos);
+        assert_eq!(k_view.shape(), &[3, 2, 4]);
+        assert_eq!(v_view.shape(), &[3, 2, 4]);
+        assert!(values(&k_view).iter().all(|&x| x == 1.0));
+
+        // Views handed out earlier keep their contents; the cache writes into its own copy
+        cache.update(&Tensor::zeros(vec![1, 2, 4], DType::F32), &k.narrow(0, 0, 1), 0);
+        assert!(values(&k_view).iter().all(|&x| x == 1.0));
+        assert_eq!(values(&cache.get_view(1).0), vec![0.0; 8]);
+    }
+
+    #[test]
+    fn test_contiguous_and_copy_from() {
+        let t = Tensor::from_vec_f32((0..6).map(|x| x as f32).collect(), vec![2, 3]);
+        let c = t.t().contiguous();
+        assert!(c.is_contiguous());
+        assert_eq!(c.strides(), &[2, 1]);
+        assert_eq!(values(&c), vec![0.0, 3.0, 1.0, 4.0, 2.0, 5.0]);
+
+        // Already-contiguous views keep sharing storage and offset
+        let row = t.narrow(0, 1, 1).contiguous();
+        assert!(Arc::ptr_eq(&row.storage, &t.storage));
+        assert_eq!(row.offset, 12);
+
+        // In-place strided write through a column view, with a broadcast source
+        let dst = Tensor::zeros(vec![2, 3], DType::F32);
+        unsafe { dst.narrow(1, 2, 1).copy_from(&Tensor::from_vec_f32(vec![7.0], vec![1])) };
+        assert_eq!(values(&dst), vec![0.0, 0.0, 7.0, 0.0, 0.0, 7.0]);
+    }
+
+    #[test]
+    fn test_kv_cache_strided_update() {
+        let mut cache = KVCache::new(4, 1, 2);
+        // [Head, Len, Dim] storage viewed as [Len, Head, Dim]
+        let k = Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 4.0], vec![1, 2, 2]).permute(&[1, 0, 2]);
+        assert!(!k.is_contiguous());
+        cache.update(&k, &k, 1);
+        assert_eq!(cache.current_pos, 3);
+        assert_eq!(values(&cache.k), vec![0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_typed_access() {
+        let t = Tensor::from_vec_f32(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2, 3]);
+        let tt = t.t();
+
+        // Indexing follows the view's strides and offset
+        assert_eq!(tt.get::<f32>(&[2, 0]).unwrap(), 3.0);
+        assert_eq!(t.narrow(0, 1, 1).get::<f32>(&[0, 1]).unwrap(), 5.0);
+        unsafe { tt.set(&[0, 1], 40.0f32).unwrap() };
+        assert_eq!(t.get::<f32>(&[1, 0]).unwrap(), 40.0);
+
+        assert_eq!(t.narrow(1, 2, 1).narrow(0, 0, 1).item::<f32>().unwrap(), 3.0);
+        assert!(matches!(t.item::<f32>(), Err(TensorError::ShapeMismatch { .. })));
+        assert!(matches!(tt.get::<f32>(&[3, 0]), Err(TensorError::OutOfBounds { .. })));
+        assert!(matches!(tt.as_slice::<f32>(), Err(TensorError::NotContiguous)));
+        assert!(matches!(t.to_vec::<i8>(), Err(TensorError::DTypeMismatch { .. })));
+
+        let h = Tensor::zeros(vec![2], DType::F16);
+        unsafe { h.set(&[1], half::f16::from_f32(2.5)).unwrap() };
+        assert_eq!(h.to_vec::<half::f16>().unwrap()[1].to_f32(), 2.5);
+    }
+
+    #[test]
+    fn test_fallible_views() {
+        let t = Tensor::zeros(vec![2, 3], DType::F32);
+        assert!(matches!(t.try_view(&[4]), Err(TensorError::ShapeMismatch { .. })));
+        assert!(matches!(t.t().try_view(&[6]), Err(TensorError::NotContiguous)));
+        assert!(matches!(t.try_narrow(1, 2, 2), Err(TensorError::OutOfBounds { .. })));
+        assert!(matches!(t.try_permute(&[0, 0]), Err(TensorError::OutOfBounds { .. })));
+        assert!(matches!(t.try_squeeze(0), Err(TensorError::ShapeMismatch { .. })));
+        assert!(matches!(t.try_expand(&[3, 3]), Err(TensorError::IncompatibleShapes { .. })));
+
+        let mut cache = KVCache::new(2, 1, 2);
+        let k = Tensor::zeros(vec![2, 1, 2], DType::F32);
+        assert!(matches!(cache.try_update(&k, &k, 1), Err(TensorError::OutOfBounds { .. })));
+        let bad = Tensor::zeros(vec![1, 2, 2], DType::F32);
+        assert!(matches!(cache.try_update(&bad, &bad, 0), Err(TensorError::ShapeMismatch { .. })));
+        assert_eq!(cache.current_pos, 0);
+
+        // A bad `v` is caught before `k` is written
+        let ones = Tensor::ones(vec![1, 1, 2], DType::F32);
+        let bad_v = Tensor::zeros(vec![1, 1, 2], DType::F16);
+        assert!(matches!(cache.try_update(&ones, &bad_v, 0), Err(TensorError::DTypeMismatch { .. })));
+        assert!(matches!(cache.try_update(&ones, &bad, 0), Err(TensorError::ShapeMismatch { .. })));
+        assert_eq!(values(&cache.k), vec![0.0; 4]);
+        assert_eq!(cache.current_pos, 0);
+    }
 }
@@ -0,0 +1,374 @@
+use std::sync::Arc;
+use std::ops::Range;
+use crate::tensor::{Tensor, Shape, Strides};
+use crate::autograd::node::Node;
+use crate::autograd::grad_mode::needs_grad;
+use crate::error::{Result, TensorError};
+use crate::ops::binary::reduce_to_shape;
+use crate::ops::strided::for_each_offset;
+
+// Zero-copy view operations.
+// Every view shares `storage` with its source and only rewrites shape / strides / offset.
+// When the source requires grad, the view records a Node mapping its gradient back.
+
+#[derive(Debug)]
+pub struct ReshapeNode {
+    input: Tensor,
+}
+
+impl Node for ReshapeNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![grad.reshape(&self.input.shape)]
+    }
+}
+
+#[derive(Debug)]
+pub struct PermuteNode {
+    input: Tensor,
+    dims: Vec<usize>,
+}
+
+impl Node for PermuteNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // Output dim i came from input dim dims[i]; undo with the inverse permutation
+        let mut inverse = vec![0; self.dims.len()];
+        for (i, &d) in self.dims.iter().enumerate() {
+            inverse[d] = i;
+        }
+        vec![grad.permute(&inverse)]
+    }
+}
+
+#[derive(Debug)]
+pub struct NarrowNode {
+    input: Tensor,
+    dim: usize,
+    start: usize,
+}
+
+impl Node for NarrowNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // Scatter grad into the narrowed window of a zero tensor shaped like the input
+        let grad_input = Tensor::zeros(self.input.shape.clone(), grad.dtype);
+        // `grad_input` is freshly allocated and not yet shared
+        unsafe { grad_input.narrow(self.dim, self.start, grad.shape[self.dim]).copy_from(grad) };
+        if needs_grad(&[grad]) {
+            let mut out = grad_input;
+            out.requires_grad = true;
+            out.ctx = Some(Arc::new(NarrowBackwardNode { grad: grad.clone(), dim: self.dim, start: self.start }));
+            return vec![out];
+        }
+        vec![grad_input]
+    }
+}
+
+/// Records the zero-padding scatter of `NarrowNode::backward` for double backward.
+#[derive(Debug)]
+pub struct NarrowBackwardNode {
+    grad: Tensor,
+    dim: usize,
+    start: usize,
+}
+
+impl Node for NarrowBackwardNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.grad.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![grad.narrow(self.dim, self.start, self.grad.shape[self.dim])]
+    }
+}
+
+#[derive(Debug)]
+pub struct ContiguousNode {
+    input: Tensor,
+}
+
+impl Node for ContiguousNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        // A copy is the identity for gradients
+        vec![grad.clone()]
+    }
+}
+
+#[derive(Debug)]
+pub struct ExpandNode {
+    input: Tensor,
+}
+
+impl Node for ExpandNode {
+    fn parents(&self) -> Vec<Tensor> {
+        vec![self.input.clone()]
+    }
+
+    fn backward(&self, grad: &Tensor) -> Vec<Tensor> {
+        vec![reduce_to_shape(grad, &self.input.shape)]
+    }
+}
+
+impl Tensor {
+    /// Builds a view sharing this tensor's storage, attaching `node` when grad is required.
+    fn make_view(&self, shape: Shape, strides: Strides, offset: usize, node: impl FnOnce() -> Arc<dyn Node>) -> Self {
+        let requires_grad = needs_grad(&[self]);
+        let mut out = Self::new(self.storage.clone(), shape, strides, offset, self.dtype, requires_grad);
+        if requires_grad {
+            out.ctx = Some(node());
+        }
+        out
+    }
+
+    fn check_dim(&self, dim: usize, ndim: usize) -> Result<()> {
+        if dim >= ndim {
+            return Err(TensorError::OutOfBounds { index: vec![dim], shape: self.shape.clone() });
+        }
+        Ok(())
+    }
+
+    fn check_numel(&self, shape: &[usize]) -> Result<()> {
+        if shape.iter().product::<usize>() != self.numel() {
+            return Err(TensorError::ShapeMismatch { expected: self.shape.clone(), found: shape.to_vec() });
+        }
+        Ok(())
+    }
+
+    /// Zero-copy reinterpretation of a contiguous tensor with a new shape.
+    /// Fails if the tensor is not contiguous; use `reshape` to allow a copy.
+    pub fn try_view(&self, shape: &[usize]) -> Result<Self> {
+        self.check_numel(shape)?;
+        if !self.is_contiguous() {
+            return Err(TensorError::NotContiguous);
+        }
+
+        Ok(self.make_view(shape.to_vec(), Self::default_strides(shape), self.offset, || {
+            Arc::new(ReshapeNode { input: self.clone() })
+        }))
+    }
+
+    pub fn view(&self, shape: &[usize]) -> Self {
+        self.try_view(shape).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Returns a tensor with the given shape, as a view when contiguous and as a copy otherwise.
+    pub fn try_reshape(&self, shape: &[usize]) -> Result<Self> {
+        self.check_numel(shape)?;
+        if self.is_contiguous() {
+            self.try_view(shape)
+        } else {
+            self.contiguous().try_view(shape)
+        }
+    }
+
+    pub fn reshape(&self, shape: &[usize]) -> Self {
+        self.try_reshape(shape).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Reorders dimensions: output dim `i` is input dim `dims[i]`.
+    pub fn try_permute(&self, dims: &[usize]) -> Result<Self> {
+        let ndim = self.shape.len();
+        let mut seen = vec![false; ndim];
+        for &d in dims {
+            if d >= ndim || seen[d] {
+                return Err(TensorError::OutOfBounds { index: dims.to_vec(), shape: self.shape.clone() });
+            }
+            seen[d] = true;
+        }
+        if dims.len() != ndim {
+            return Err(TensorError::ShapeMismatch { expected: self.shape.clone(), found: dims.to_vec() });
+        }
+
+        let shape = dims.iter().map(|&d| self.shape[d]).collect();
+        let strides = dims.iter().map(|&d| self.strides[d]).collect();
+        Ok(self.make_view(shape, strides, self.offset, || {
+            Arc::new(PermuteNode { input: self.clone(), dims: dims.to_vec() })
+        }))
+    }
+
+    pub fn permute(&self, dims: &[usize]) -> Self {
+        self.try_permute(dims).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Swaps two dimensions.
+    pub fn try_transpose(&self, dim0: usize, dim1: usize) -> Result<Self> {
+        let ndim = self.shape.len();
+        self.check_dim(dim0, ndim)?;
+        self.check_dim(dim1, ndim)?;
+        let mut dims: Vec<usize> = (0..ndim).collect();
+        dims.swap(dim0, dim1);
+        self.try_permute(&dims)
+    }
+
+    pub fn transpose(&self, dim0: usize, dim1: usize) -> Self {
+        self.try_transpose(dim0, dim1).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Restricts `dim` to `len` elements starting at `start`, by moving the byte offset.
+    pub fn try_narrow(&self, dim: usize, start: usize, len: usize) -> Result<Self> {
+        self.check_dim(dim, self.shape.len())?;
+        if start + len > self.shape[dim] {
+            let mut index = vec![0; self.shape.len()];
+            index[dim] = start + len;
+            return Err(TensorError::OutOfBounds { index, shape: self.shape.clone() });
+        }
+
+        let mut shape = self.shape.clone();
+        shape[dim] = len;
+        let offset = self.offset + start * self.strides[dim] * self.dtype.size_of();
+        Ok(self.make_view(shape, self.strides.clone(), offset, || {
+            Arc::new(NarrowNode { input: self.clone(), dim, start })
+        }))
+    }
+
+    pub fn narrow(&self, dim: usize, start: usize, len: usize) -> Self {
+        self.try_narrow(dim, start, len).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Slices `dim` by a half-open range. Equivalent to `narrow(dim, range.start, range.len())`.
+    pub fn try_slice(&self, dim: usize, range: Range<usize>) -> Result<Self> {
+        if range.start > range.end {
+            return Err(TensorError::OutOfBounds { index: vec![range.start, range.end], shape: self.shape.clone() });
+        }
+        self.try_narrow(dim, range.start, range.end - range.start)
+    }
+
+    pub fn slice(&self, dim: usize, range: Range<usize>) -> Self {
+        self.try_slice(dim, range).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Removes `dim`, which must have size 1.
+    pub fn try_squeeze(&self, dim: usize) -> Result<Self> {
+        self.check_dim(dim, self.shape.len())?;
+        let mut shape = self.shape.clone();
+        let mut strides = self.strides.clone();
+        if shape.remove(dim) != 1 {
+            return Err(TensorError::ShapeMismatch { expected: vec![1], found: vec![self.shape[dim]] });
+        }
+        strides.remove(dim);
+        Ok(self.make_view(shape, strides, self.offset, || {
+            Arc::new(ReshapeNode { input: self.clone() })
+        }))
+    }
+
+    pub fn squeeze(&self, dim: usize) -> Self {
+        self.try_squeeze(dim).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Inserts a size-1 dimension at `dim`.
+    pub fn try_unsqueeze(&self, dim: usize) -> Result<Self> {
+        self.check_dim(dim, self.shape.len() + 1)?;
+        let mut shape = self.shape.clone();
+        let mut strides = self.strides.clone();
+        // Any stride is valid for a size-1 dim; pick the dense one
+        let stride = if dim < self.shape.len() { self.strides[dim] * self.shape[dim] } else { 1 };
+        shape.insert(dim, 1);
+        strides.insert(dim, stride);
+        Ok(self.make_view(shape, strides, self.offset, || {
+            Arc::new(ReshapeNode { input: self.clone() })
+        }))
+    }
+
+    pub fn unsqueeze(&self, dim: usize) -> Self {
+        self.try_unsqueeze(dim).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Broadcasts this tensor to `shape` with stride-0 dims, without copying.
+    pub fn try_expand(&self, shape: &[usize]) -> Result<Self> {
+        if Self::broadcast_shape(&self.shape, shape).as_deref() != Some(shape) {
+            return Err(TensorError::IncompatibleShapes { lhs: self.shape.clone(), rhs: shape.to_vec() });
+        }
+        let strides = self.broadcast_strides(shape);
+        Ok(self.make_view(shape.to_vec(), strides, self.offset, || {
+            Arc::new(ExpandNode { input: self.clone() })
+        }))
+    }
+
+    pub fn expand(&self, shape: &[usize]) -> Self {
+        self.try_expand(shape).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+// Materialization.
+// Kernels that need dense row-major input call `contiguous()` instead of assuming a layout.
+
+/// Copies `numel` elements of `elem` bytes between two strided layouts of the same shape.
+/// Offsets are in elements relative to `src` / `dst`.
+unsafe fn strided_copy(shape: &[usize], src: *const u8, src_strides: &[usize], dst: *mut u8, dst_strides: &[usize], elem: usize) {
+    let dense = Tensor::default_strides(shape);
+    if src_strides == dense.as_slice() && dst_strides == dense.as_slice() {
+        // Both dense: one block copy. `copy` rather than `copy_nonoverlapping` since views may alias.
+        std::ptr::copy(src, dst, shape.iter().product::<usize>() * elem);
+        return;
+    }
+    for_each_offset(shape, [src_strides, dst_strides], |_, [s, d]| {
+        std::ptr::copy(src.add(s * elem), dst.add(d * elem), elem);
+    });
+}
+
+impl Tensor {
+    /// Returns a dense row-major tensor with the same values.
+    /// Already-contiguous tensors are returned as a cheap clone sharing storage (and offset).
+    pub fn contiguous(&self) -> Self {
+        if self.is_contiguous() {
+            return self.clone();
+        }
+
+        let mut out = Self::zeros(self.shape.clone(), self.dtype);
+        unsafe { out.copy_from(self) };
+        if needs_grad(&[self]) {
+            out.requires_grad = true;
+            out.ctx = Some(Arc::new(ContiguousNode { input: self.clone() }));
+        }
+        out
+    }
+
+    /// Copies `src` into this tensor's memory in place, honoring both layouts.
+    /// `src` is broadcast to this tensor's shape. The write is not recorded in the graph.
+    ///
+    /// # Safety
+    /// Clones and views share storage, so the caller must ensure that no slice borrowed with
+    /// [`as_slice`](Self::as_slice) covers the written elements and that no other thread
+    /// accesses them during the copy. `src` may not overlap this tensor's elements.
+    pub unsafe fn try_copy_from(&self, src: &Tensor) -> Result<()> {
+        if self.dtype != src.dtype {
+            return Err(TensorError::DTypeMismatch { expected: self.dtype, found: src.dtype });
+        }
+        if Self::broadcast_shape(&src.shape, &self.shape).as_deref() != Some(self.shape.as_slice()) {
+            return Err(TensorError::IncompatibleShapes { lhs: self.shape.clone(), rhs: src.shape.clone() });
+        }
+
+        let src_strides = src.broadcast_strides(&self.shape);
+        strided_copy(
+            &self.shape,
+            src.storage.as_ptr().add(src.offset),
+            &src_strides,
+            self.storage.as_ptr().add(self.offset) as *mut u8,
+            &self.strides,
+            self.dtype.size_of(),
+        );
+        Ok(())
+    }
+
+    /// Panicking form of [`try_copy_from`](Self::try_copy_from).
+    ///
+    /// # Safety
+    /// Same contract as [`try_copy_from`](Self::try_copy_from).
+    pub unsafe fn copy_from(&self, src: &Tensor) {
+        self.try_copy_from(src).unwrap_or_else(|e| panic!("{}", e))
+    }
+}